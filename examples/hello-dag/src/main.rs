@@ -12,7 +12,7 @@ fn main() {
     let mut dag = Dag::new();
     for _ in 1..100 {
         let node_id: u32 = rng.gen();
-        let new_node = Node::new(node_id, None, None);
+        let new_node = Node::new(node_id, None, None, ());
         println!("Created new node : {:?}", new_node);
         dag.insert(new_node);
         println!("Inserted new node {:?} to the DAG.", dag.get(&node_id).expect("Invalid value assumption."));
@@ -1,25 +1,126 @@
-use core::hash::Hash;
-use crate::Node;
+use core::{
+    hash::{
+        Hash,
+        Hasher,
+    },
+    fmt::{
+        self,
+        Debug,
+    },
+};
+use std::sync::Arc;
+use crate::DagNode;
 
-/// `CollidingNode<T>` is a struct wrapper similar and exchangeable to `Node<T>`. Whereas normal `Node<T>`
+/// `CollidingNode<N>` is a struct wrapper similar and exchangeable to `Node<T>` or any other `DagNode`. Whereas normal `Node<T>`
 /// are defined to be equal if the id is equal, `CollingNode<T>` will be different if *any* field of the node is different.
-#[derive(Debug, Clone, Hash, Copy, PartialEq, Eq)]
-pub struct CollidingNode<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy>(pub Node<T, U>);
+/// Payloads are compared through the `DagNode::payload_eq` and `DagNode::payload_hash` hooks.
+#[derive(Debug, Clone, Copy)]
+pub struct CollidingNode<N: DagNode>(pub N);
 
-impl<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> From<Node<T, U>> for CollidingNode<T, U> {
-    fn from(node: Node<T, U>) -> Self  {
+impl<N: DagNode> PartialEq for CollidingNode<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.has_same_fields_to(&other.0)
+    }
+}
+
+impl<N: DagNode> Eq for CollidingNode<N> {}
+
+impl<N: DagNode> Hash for CollidingNode<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.id().hash(state);
+        for reference in self.0.references() {
+            reference.hash(state);
+        };
+        self.0.payload_hash(state);
+    }
+}
+
+impl<N: DagNode> From<N> for CollidingNode<N> {
+    fn from(node: N) -> Self  {
         CollidingNode(node)
     }
 }
 
-impl<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> CollidingNode<T, U> {
-    pub fn has_same_fields_to(&self, node: &Node<T, U>) -> bool {
-        self.0.id == node.id
+impl<N: DagNode> CollidingNode<N> {
+    /// Compares the id, the references (with their branches) and the payload of the colliding node to the node presented as the argument.
+    pub fn has_same_fields_to(&self, node: &N) -> bool {
+        self.0.id() == node.id()
         &&
-        self.0.left == node.left
+        self.0.references().eq(node.references())
         &&
-        self.0.right == node.right
-        &&
-        self.0.payload == node.payload
+        self.0.payload_eq(node)
     }
-}
\ No newline at end of file
+}
+
+/// Merge function of `CollitionPolicy::Merge`, called on the present node and the newcomer, in this order.
+pub type MergeFn<N> = Arc<dyn Fn(&N, &N) -> N + Send + Sync>;
+
+/// Policy applied by `Dag::insert` when the id of the inserted node is already present in the dag.
+#[derive(Default)]
+pub enum CollitionPolicy<N> {
+    /// Keeps the present node and collects the newcomer as a possible collition.
+    #[default]
+    KeepFirst,
+    /// Replaces the present node with the newcomer, unless the newcomer's references would close a cycle.
+    /// Refused newcomers are collected as possible collitions.
+    KeepLast,
+    /// Keeps the present node and drops the newcomer.
+    Reject,
+    /// Replaces the present node with the result of merging the present node and the newcomer, in this order,
+    /// unless the merged node changes the id or its references would close a cycle.
+    /// Refused merges collect the newcomer as a possible collition. See `CollitionPolicy::merge` to build it from a closure.
+    Merge(MergeFn<N>),
+}
+
+impl<N> CollitionPolicy<N> {
+    /// Builds a `Merge` policy from a merge function, which may capture state.
+    pub fn merge<F: Fn(&N, &N) -> N + Send + Sync + 'static>(merge: F) -> Self {
+        CollitionPolicy::Merge(Arc::new(merge))
+    }
+}
+
+impl<N> Clone for CollitionPolicy<N> {
+    fn clone(&self) -> Self { // implemented by hand, as `N` doesn't need to be `Clone` for the policy to be.
+        match self {
+            Self::KeepFirst => Self::KeepFirst,
+            Self::KeepLast => Self::KeepLast,
+            Self::Reject => Self::Reject,
+            Self::Merge(merge) => Self::Merge(Arc::clone(merge)),
+        }
+    }
+}
+
+impl<N> Debug for CollitionPolicy<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeepFirst => write!(f, "KeepFirst"),
+            Self::KeepLast => write!(f, "KeepLast"),
+            Self::Reject => write!(f, "Reject"),
+            Self::Merge(_) => write!(f, "Merge(..)"),
+        }
+    }
+}
+
+/// Outcome of a collition handled by `Dag::insert`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution<T> {
+    /// The present node was kept and the newcomer collected.
+    KeptFirst,
+    /// The newcomer replaced the present node.
+    KeptLast,
+    /// The newcomer was dropped.
+    Rejected,
+    /// The merged node replaced the present node.
+    Merged,
+    /// The replacement was refused because it would close `cycle`, in edge order starting by the colliding id, and the newcomer was collected.
+    CycleRefused { cycle: Vec<T> },
+    /// The merge was refused because the merge function returned a node with `merged_id` instead of the colliding id, and the newcomer was collected.
+    MergeRefused { merged_id: T },
+}
+
+/// Audit record of a collition handled by `Dag::insert`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollitionDecision<T> {
+    pub id: T,
+    pub resolution: Resolution<T>,
+}
@@ -0,0 +1,387 @@
+use core::fmt::Debug;
+use std::collections::{
+    HashMap,
+    HashSet,
+    VecDeque,
+};
+use crate::{
+    Node,
+    DagNode,
+    Dag,
+    indexed,
+    error::{
+        TopologicalError,
+        CsrError,
+    },
+    topological::PathLength,
+    validation::{
+        DanglingReference,
+        SliceIndex,
+        ValidationReport,
+    },
+};
+
+/// `CsrDag<N>` is a frozen, read-only layout of a DAG for large analyses.
+/// Nodes are stored in a vector and addressed by dense `u32` indices, and both the references and the outgoing edges of every node
+/// are stored in compressed sparse rows, i.e. a single vector of indices plus the offset where the row of each node starts.
+/// Edges keep their multiplicity, so a node referencing the same ancestor twice has two edges to it, as in `Topology`.
+/// Accessors return `None` for indices out of range, as `Topology` does for unknown ids.
+#[derive(Debug, Clone)]
+pub struct CsrDag<N: DagNode> {
+    nodes: Vec<N>,
+    indices: HashMap<N::Id, u32>,
+    parent_offsets: Vec<u32>,
+    parents: Vec<u32>,
+    child_offsets: Vec<u32>,
+    children: Vec<u32>,
+}
+
+impl<N: DagNode + Clone + Debug> CsrDag<N> {
+    /// Builds the layout from a list of nodes, indexed in the order they were given.
+    /// The list is validated as `Topology::try_from_slice` does, so it is refused with the same `ValidationReport` if it has dangling references,
+    /// repeated ids or collitions. Lists with more nodes or edges than `u32` indices can address are refused as `CsrError::TooLarge`.
+    pub fn try_from_slice(node_list: &[N]) -> Result<Self, CsrError<N::Id>> {
+        check_capacity(node_list.len(), 0)?;
+        let slice_index = SliceIndex::new(node_list);
+        let report = slice_index.report(node_list);
+        if !report.is_consistent() {
+            return Err(CsrError::Invalid(report));
+        };
+        // a consistent list has no repeated ids, so the nodes are indexed by position.
+        let indices: HashMap<N::Id, u32> = slice_index.positions.into_iter().map(|(id, position)| { (id, position as u32) }).collect();
+        Self::from_indexed_nodes(node_list.to_vec(), indices)
+    }
+    /// Builds the layout from the nodes of a dag, indexed following `Dag::order`.
+    /// If nodes in the dag have unresolved references, the `ValidationReport` listing them is returned.
+    pub fn try_from_dag(dag: &Dag<N>) -> Result<Self, CsrError<N::Id>> {
+        let safety_report = dag.safety_report();
+        if !safety_report.unresolved_references.is_empty() {
+            let mut report = ValidationReport::new();
+            report.dangling_references = safety_report.unresolved_references;
+            return Err(CsrError::Invalid(report));
+        };
+        check_capacity(dag.len(), 0)?;
+        let nodes: Vec<N> = dag.nodes().cloned().collect();
+        let indices: HashMap<N::Id, u32> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| { (node.id(), index as u32) })
+            .collect();
+        Self::from_indexed_nodes(nodes, indices)
+    }
+    /// Lays out the rows of a list of nodes whose references are all indexed.
+    fn from_indexed_nodes(nodes: Vec<N>, indices: HashMap<N::Id, u32>) -> Result<Self, CsrError<N::Id>> {
+        check_capacity(nodes.len(), nodes.iter().map(|node| { node.in_degree() }).sum())?;
+        let mut parent_offsets: Vec<u32> = Vec::with_capacity(nodes.len() + 1);
+        let mut parents: Vec<u32> = Vec::new();
+        let mut out_degrees: Vec<u32> = vec![0; nodes.len()];
+        parent_offsets.push(0);
+        for node in nodes.iter() {
+            for ancestor in node.parents() {
+                let ancestor_index = indices[&ancestor];
+                parents.push(ancestor_index);
+                out_degrees[ancestor_index as usize] += 1;
+            };
+            parent_offsets.push(parents.len() as u32);
+        };
+        let mut child_offsets: Vec<u32> = Vec::with_capacity(nodes.len() + 1);
+        child_offsets.push(0);
+        for out_degree in out_degrees.iter() {
+            child_offsets.push(child_offsets[child_offsets.len() - 1] + out_degree);
+        };
+        let mut children: Vec<u32> = vec![0; parents.len()];
+        let mut next_slot: Vec<u32> = child_offsets[..nodes.len()].to_vec();
+        for index in 0..nodes.len() {
+            for ancestor_index in &parents[parent_offsets[index] as usize..parent_offsets[index + 1] as usize] {
+                let slot = &mut next_slot[*ancestor_index as usize];
+                children[*slot as usize] = index as u32;
+                *slot += 1;
+            };
+        };
+        Ok(CsrDag {
+            nodes,
+            indices,
+            parent_offsets,
+            parents,
+            child_offsets,
+            children,
+        })
+    }
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns `true` if the layout has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.parents.len()
+    }
+    /// Gets the index of a node by id.
+    pub fn index_of(&self, id: &N::Id) -> Option<u32> {
+        self.indices.get(id).copied()
+    }
+    /// Gets the node at an index.
+    pub fn node(&self, index: u32) -> Option<&N> {
+        self.nodes.get(index as usize)
+    }
+    /// Gets the nodes, ordered by index.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+    /// Gets the indices of the nodes referenced by the node at an index, once per reference.
+    pub fn parents(&self, index: u32) -> Option<&[u32]> {
+        self.contains(index).then(|| { self.parent_row(index as usize) })
+    }
+    /// Gets the indices of the nodes referencing the node at an index, once per reference.
+    pub fn children(&self, index: u32) -> Option<&[u32]> {
+        self.contains(index).then(|| { self.child_row(index as usize) })
+    }
+    fn contains(&self, index: u32) -> bool {
+        (index as usize) < self.nodes.len()
+    }
+    fn parent_row(&self, index: usize) -> &[u32] {
+        &self.parents[self.parent_offsets[index] as usize..self.parent_offsets[index + 1] as usize]
+    }
+    fn child_row(&self, index: usize) -> &[u32] {
+        &self.children[self.child_offsets[index] as usize..self.child_offsets[index + 1] as usize]
+    }
+    /// Sorts the indices in a topological order with Kahn's algorithm, in O(V + E).
+    /// If the layout has a cycle it returns a `TopologicalError::Cycle` with one of its cycles and all the ids that could not be ordered, by index.
+    pub fn sort(&self) -> Result<Vec<u32>, TopologicalError<N::Id>> {
+        indexed::sort(
+            self.nodes.len(),
+            |index| { self.parent_row(index).iter().map(|parent| { *parent as usize }) },
+            |index| { self.child_row(index).iter().map(|child| { *child as usize }) },
+        )
+        .map(|ordering| { ordering.into_iter().map(|index| { index as u32 }).collect() })
+        .map_err(|unsorted| {
+            TopologicalError::Cycle {
+                cycle: unsorted.cycle.into_iter().map(|index| { self.nodes[index].id() }).collect(),
+                unordered: unsorted.unordered.into_iter().map(|index| { self.nodes[index].id() }).collect(),
+            }
+        })
+    }
+    /// Calculates the shortest and longest path lengths from the node at `source` to every node, by index, relaxing the edges in topological order.
+    /// Nodes unreachable from the source have `(None, None)`. Returns `Ok(None)` if the source is out of range.
+    pub fn shortest_and_longest_paths(&self, source: u32) -> Result<Option<Vec<PathLength>>, TopologicalError<N::Id>> {
+        if !self.contains(source) {
+            return Ok(None);
+        };
+        let ordering = self.sort()?;
+        let mut lengths: Vec<PathLength> = vec![(None, None); self.nodes.len()];
+        lengths[source as usize] = (Some(0), Some(0));
+        for index in ordering {
+            let (Some(shortest), Some(longest)) = lengths[index as usize] else {
+                continue;
+            };
+            for child in self.child_row(index as usize) {
+                let child_lengths = &mut lengths[*child as usize];
+                child_lengths.0 = Some(child_lengths.0.map_or(shortest + 1, |child_shortest| { child_shortest.min(shortest + 1) }));
+                child_lengths.1 = Some(child_lengths.1.map_or(longest + 1, |child_longest| { child_longest.max(longest + 1) }));
+            };
+        };
+        Ok(Some(lengths))
+    }
+    /// Evaluates all the paths starting at the node at `source`, as `Topology::bfs_all_paths` does, by index.
+    /// Returns `None` if the source is out of range.
+    pub fn all_paths(&self, source: u32) -> Option<Vec<Vec<u32>>> {
+        if !self.contains(source) {
+            return None;
+        };
+        let mut paths: Vec<Vec<u32>> = Vec::new();
+        let mut stack: Vec<Vec<u32>> = vec![vec![source]];
+        while let Some(path) = stack.pop() {
+            let last = path[path.len() - 1];
+            for child in self.child_row(last as usize).iter().rev() { // reversed, so paths are visited in the order of the edges.
+                let mut new_path = path.clone();
+                new_path.push(*child);
+                stack.push(new_path);
+            };
+            if path.len() > 1 {
+                paths.push(path);
+            };
+        };
+        Some(paths)
+    }
+    /// Visits the descendants of the node at `source` in breadth-first order, by index. Returns `None` if the source is out of range.
+    pub fn descendants(&self, source: u32) -> Option<Vec<u32>> {
+        self.breadth_first(source, |index| { self.child_row(index as usize) })
+    }
+    /// Visits the ancestors of the node at `source` in breadth-first order, by index. Returns `None` if the source is out of range.
+    pub fn ancestors(&self, source: u32) -> Option<Vec<u32>> {
+        self.breadth_first(source, |index| { self.parent_row(index as usize) })
+    }
+    fn breadth_first<'a>(&'a self, source: u32, neighbours: impl Fn(u32) -> &'a [u32]) -> Option<Vec<u32>> {
+        if !self.contains(source) {
+            return None;
+        };
+        let mut visited: Vec<bool> = vec![false; self.nodes.len()];
+        visited[source as usize] = true;
+        let mut queue: VecDeque<u32> = VecDeque::from([source]);
+        let mut visit: Vec<u32> = Vec::new();
+        while let Some(index) = queue.pop_front() {
+            for neighbour in neighbours(index) {
+                if !visited[*neighbour as usize] {
+                    visited[*neighbour as usize] = true;
+                    visit.push(*neighbour);
+                    queue.push_back(*neighbour);
+                };
+            };
+        };
+        Some(visit)
+    }
+}
+
+/// Checks that a layout with the given number of nodes and edges can be addressed by `u32` indices.
+fn check_capacity<T>(nodes: usize, edges: usize) -> Result<(), CsrError<T>> {
+    if u32::try_from(nodes).is_ok() && u32::try_from(edges).is_ok() {
+        Ok(())
+    } else {
+        Err(CsrError::TooLarge { nodes, edges })
+    }
+}
+
+impl<N: DagNode + Clone + Debug> TryFrom<&Dag<N>> for CsrDag<N> {
+    type Error = CsrError<N::Id>;
+    fn try_from(dag: &Dag<N>) -> Result<Self, Self::Error> {
+        Self::try_from_dag(dag)
+    }
+}
+
+#[test]
+fn csr_rows_of_small_dag() {
+    let node_list = [
+        Node::new(0, None, None, ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(2, None, Some(0), ()),
+        Node::new(3, Some(0), Some(1), ()),
+        Node::new(4, Some(2), Some(1), ()),
+        Node::new(5, Some(3), Some(4), ()),
+    ];
+    let csr = CsrDag::try_from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    assert_eq!(csr.len(), 6);
+    assert_eq!(csr.edge_count(), 8);
+    assert_eq!(csr.parents(3), Some(&[0, 1][..]));
+    assert_eq!(csr.children(0), Some(&[1, 2, 3][..]));
+    assert_eq!(csr.children(1), Some(&[3, 4][..]));
+    assert_eq!(csr.children(5), Some(&[][..]));
+    assert_eq!(csr.children(6), None);
+    assert_eq!(csr.node(6), None);
+    assert_eq!(csr.index_of(&4), Some(4));
+    assert_eq!(csr.node(4).expect("Invalid value assumption.").id, 4);
+    let Ok(ordering) = csr.sort() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(ordering, vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(csr.descendants(1), Some(vec![3, 4, 5]));
+    assert_eq!(csr.ancestors(5), Some(vec![3, 4, 0, 1, 2]));
+    assert_eq!(csr.ancestors(6), None);
+    assert!(matches!(csr.shortest_and_longest_paths(6), Ok(None)));
+    assert_eq!(csr.all_paths(6), None);
+}
+
+#[test]
+fn csr_analysis_matches_topology() {
+    let node_list = [
+        Node::new(35, None, None, ()),
+        Node::new(42, Some(35), None, ()),
+        Node::new(32, None, Some(35), ()),
+        Node::new(51, Some(42), None, ()),
+        Node::new(101, Some(32), Some(51), ()),
+        Node::new(52, Some(51), Some(32), ()),
+        Node::new(50, Some(42), None, ()),
+        Node::new(1, Some(50), Some(52), ()),
+        Node::new(0, Some(50), Some(1), ()),
+    ];
+    let csr = CsrDag::try_from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    let Ok(Some(topology_lengths)) = crate::topological::Topology::shortest_and_longest_paths(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    let Ok(Some(csr_lengths)) = csr.shortest_and_longest_paths(0) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    for (index, lengths) in csr_lengths.iter().enumerate() {
+        assert_eq!(topology_lengths[&csr.nodes()[index].id], *lengths);
+    };
+    let topology = crate::topological::Topology::from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    let topology_paths = crate::topological::Topology::bfs_all_paths(&topology, 35).expect("Invalid value assumption.");
+    let csr_paths: Vec<Vec<u32>> = csr
+        .all_paths(0)
+        .expect("Invalid value assumption.")
+        .into_iter()
+        .map(|path| { path.into_iter().map(|index| { csr.nodes()[index as usize].id }).collect() })
+        .collect();
+    assert_eq!(csr_paths, topology_paths);
+}
+
+#[test]
+fn csr_reports_invalid_lists_and_cycles() {
+    let Err(CsrError::Invalid(report)) = CsrDag::try_from_slice(&[Node::new(0, None, None, ()), Node::new(1, Some(2), None, ()), Node::new(0, Some(1), None, ())]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(report.dangling_references, vec![DanglingReference { id: 1, side: crate::Side::Left, missing: 2 }]);
+    assert_eq!(report.collitions, vec![0]);
+    assert!(report.repeated_ids.is_empty());
+    let csr = CsrDag::try_from_slice(&[Node::new(0, Some(2), None, ()), Node::new(1, Some(0), None, ()), Node::new(2, Some(1), None, ()), Node::new(3, None, None, ())])
+        .expect("Invalid topological assumptions for this test data.");
+    let Err(TopologicalError::Cycle { cycle, unordered }) = csr.sort() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(cycle, vec![1, 2, 0]);
+    assert_eq!(unordered, vec![0, 1, 2]);
+}
+
+#[test]
+fn csr_from_dag_follows_its_order() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[Node::new(2, Some(1), None, ()), Node::new(1, Some(0), None, ()), Node::new(0, None, None, ())]);
+    let csr = CsrDag::try_from(&dag).expect("Invalid topological assumptions for this test data.");
+    assert_eq!(csr.nodes().iter().map(|node| { node.id }).collect::<Vec<u32>>(), vec![0, 1, 2]);
+    assert_eq!(csr.children(0), Some(&[1][..]));
+    dag.insert(Node::new(3, Some(4), None, ()));
+    let Err(CsrError::Invalid(report)) = CsrDag::try_from(&dag) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(report.dangling_references, vec![DanglingReference { id: 3, side: crate::Side::Left, missing: 4 }]);
+}
+
+#[test]
+fn csr_sorts_long_reversed_chain() {
+    let length: u32 = 200_000;
+    let node_list: Vec<Node<u32, ()>> = (0..length).rev().map(|id| { Node::new(id, id.checked_sub(1), None, ()) }).collect();
+    let csr = CsrDag::try_from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    let Ok(ordering) = csr.sort() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(csr.nodes()[ordering[0] as usize].id, 0);
+    assert_eq!(csr.nodes()[ordering[ordering.len() - 1] as usize].id, length - 1);
+    let Ok(Some(lengths)) = csr.shortest_and_longest_paths(ordering[0]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[0], (Some(length as usize - 1), Some(length as usize - 1)));
+}
+
+
+#[test]
+fn csr_validates_like_topology() {
+    let node_lists = [
+        vec![Node::new(0, None, None, ()), Node::new(1, Some(0), None, ()), Node::new(1, Some(0), None, ())],
+        vec![Node::new(0, None, None, ()), Node::new(1, Some(0), None, ()), Node::new(1, None, Some(0), ()), Node::new(1, Some(0), None, ())],
+        vec![Node::new(2, Some(2), Some(3), ()), Node::new(0, None, None, ()), Node::new(0, None, None, ()), Node::new(4, Some(0), Some(0), ())],
+    ];
+    for node_list in node_lists.iter() {
+        let Err(topology_report) = crate::topological::Topology::try_from_slice(node_list) else {
+            panic!("Invalid topological assumptions for this test data.")
+        };
+        let Err(CsrError::Invalid(csr_report)) = CsrDag::try_from_slice(node_list) else {
+            panic!("Invalid topological assumptions for this test data.")
+        };
+        assert_eq!(csr_report, topology_report);
+    };
+    assert_eq!(check_capacity::<u32>(u32::MAX as usize, 0), Ok(()));
+    assert_eq!(check_capacity::<u32>(u32::MAX as usize + 1, 0), Err(CsrError::TooLarge { nodes: u32::MAX as usize + 1, edges: 0 }));
+    assert_eq!(check_capacity::<u32>(2, u32::MAX as usize + 1), Err(CsrError::TooLarge { nodes: 2, edges: u32::MAX as usize + 1 }));
+}
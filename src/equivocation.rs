@@ -0,0 +1,60 @@
+use serde::{Serialize, Deserialize};
+use crate::{
+    DagNode,
+    Side,
+};
+
+/// Field-level differences of a conflicting node against the node stored in the dag.
+/// Diffs are ordered field by field, so equivocations can be listed in a stable order.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FieldDiff<T> {
+    /// References of the stored node that the conflicting node doesn't have, with their branches.
+    pub stored_only_references: Vec<(Side, T)>,
+    /// References of the conflicting node that the stored node doesn't have, with their branches.
+    pub conflicting_only_references: Vec<(Side, T)>,
+    /// Whether the payloads differ, according to `DagNode::payload_eq`.
+    pub payload_differs: bool,
+}
+
+impl<T: PartialEq + Copy> FieldDiff<T> {
+    /// Compares the conflicting node to the stored node field by field.
+    pub fn between<N: DagNode<Id = T>>(stored: &N, conflicting: &N) -> Self {
+        let stored_references: Vec<(Side, T)> = stored.references().collect();
+        let conflicting_references: Vec<(Side, T)> = conflicting.references().collect();
+        FieldDiff {
+            stored_only_references: stored_references
+                .iter()
+                .filter(|reference| { !conflicting_references.contains(reference) })
+                .copied()
+                .collect(),
+            conflicting_only_references: conflicting_references
+                .iter()
+                .filter(|reference| { !stored_references.contains(reference) })
+                .copied()
+                .collect(),
+            payload_differs: !stored.payload_eq(conflicting),
+        }
+    }
+    /// Returns `true` if the nodes have the same references and payload.
+    pub fn is_empty(&self) -> bool {
+        self.stored_only_references.is_empty()
+        && self.conflicting_only_references.is_empty()
+        && !self.payload_differs
+    }
+}
+
+/// Evidence of an equivocation, i.e. two different nodes produced with the same id.
+/// It serializes both conflicting nodes, so it can be exported and checked by third parties.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Equivocation<T, N> {
+    pub id: T,
+    /// Node stored in the dag.
+    pub stored: N,
+    /// Variant that lost the collition, whatever the `CollitionPolicy`, i.e. the collected or dropped newcomer, or the replaced node.
+    pub conflicting: N,
+    pub diff: FieldDiff<T>,
+    /// Nodes in the dag building on the stored variant, i.e. its descendants but those building only on a conflicting variant.
+    pub stored_descendants: Vec<T>,
+    /// Nodes in the dag building on the conflicting variant, i.e. the nodes referencing the id while the conflicting variant was stored, and their descendants.
+    pub conflicting_descendants: Vec<T>,
+}
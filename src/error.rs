@@ -1,22 +1,28 @@
 use std::error::Error;
 use core::{
     fmt,
+    fmt::Debug,
     num::ParseIntError,
 };
+use crate::validation::ValidationReport;
 
 #[derive(Debug)]
-pub enum TopologicalError {
+pub enum TopologicalError<T> {
     Custom,
     RepeatedNodes,
     InvalidTopologicalAssumptions,
     NotADag,
     FirstNodeHasIncomingEdges,
     ParseIntError(ParseIntError),
+    /// The list contains at least one cycle.
+    /// `cycle` lists the ids forming one of them in edge order (each id is a parent of the next one, and the last id is a parent of the first one),
+    /// and `unordered` lists every node id that could not be placed in a topological order, in the order they were given.
+    Cycle { cycle: Vec<T>, unordered: Vec<T> },
 }
 
-impl Error for TopologicalError {}
+impl<T: Debug> Error for TopologicalError<T> {}
 
-impl fmt::Display for TopologicalError {
+impl<T: Debug> fmt::Display for TopologicalError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Custom => write!(f, "Custom error"),
@@ -25,19 +31,146 @@ impl fmt::Display for TopologicalError {
             Self::NotADag => write!(f, "Provided list does not conform to a DAG."),
             Self::FirstNodeHasIncomingEdges => write!(f, "List assumptions are not met, i.e. first node should not have incoming edges."),
             Self::ParseIntError(e) => write!(f, "ParseIntError {e}"),
+            Self::Cycle { cycle, unordered } => write!(f, "Provided list does not conform to a DAG, i.e. it has the cycle {cycle:?} and {} nodes could not be ordered.", unordered.len()),
         }
     }
 }
 
-impl From<ParseIntError> for TopologicalError {
+impl<T> From<ParseIntError> for TopologicalError<T> {
     fn from(value: ParseIntError) -> Self {
         Self::ParseIntError(value)
     }
 }
 
+/// Errors returned by `Dag::remove`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum RemovalError<T> {
+    /// The id is not present in the dag.
+    NotFound(T),
+    /// The node is referenced by other nodes and the `RemovalPolicy::Refuse` policy was selected.
+    HasDescendants { id: T, children: Vec<T> },
+}
+
+impl<T: Debug> Error for RemovalError<T> {}
+
+impl<T: Debug> fmt::Display for RemovalError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "Node {id:?} is not present in the dag."),
+            Self::HasDescendants { id, children } => write!(f, "Node {id:?} can't be removed because it is referenced by {children:?}."),
+        }
+    }
+}
+
+/// Errors returned by the strict insertion methods of `Dag`, i.e. `try_insert`, `insert_or_hold` and `insert_or_update`.
+/// The node is not inserted when any of them is returned.
+#[derive(Debug, PartialEq, Eq)]
+pub enum InsertError<T> {
+    /// A node with the same id and the same fields is already present.
+    DuplicateId(T),
+    /// A node with the same id but different fields is already present.
+    Collition(T),
+    /// The node's left reference is not present in the dag.
+    MissingLeftParent { id: T, missing: T },
+    /// The node's right reference is not present in the dag.
+    MissingRightParent { id: T, missing: T },
+    /// The reference at `position` in the node's parents list is not present in the dag.
+    MissingParent { id: T, position: usize, missing: T },
+    /// The node's references would close `cycle`, given in edge order starting by the node's id.
+    Cycle { id: T, cycle: Vec<T> },
+}
+
+impl<T: Debug> Error for InsertError<T> {}
+
+impl<T: Debug> fmt::Display for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateId(id) => write!(f, "Node {id:?} is already present in the dag."),
+            Self::Collition(id) => write!(f, "A different node with id {id:?} is already present in the dag."),
+            Self::MissingLeftParent { id, missing } => write!(f, "Left reference {missing:?} of node {id:?} is not present in the dag."),
+            Self::MissingRightParent { id, missing } => write!(f, "Right reference {missing:?} of node {id:?} is not present in the dag."),
+            Self::MissingParent { id, position, missing } => write!(f, "Reference {missing:?} at position {position} of node {id:?} is not present in the dag."),
+            Self::Cycle { id, cycle } => write!(f, "The references of node {id:?} would close the cycle {cycle:?}."),
+        }
+    }
+}
+
+/// Errors returned by the in-place updates of `Dag`, i.e. `set_parents` and `set_payload`. The dag is untouched when any of them is returned.
+#[derive(Debug, PartialEq, Eq)]
+pub enum UpdateError<T> {
+    /// The id is not present in the dag.
+    NotFound(T),
+    /// The new references would close `cycle`, given in edge order starting by the node's id.
+    Cycle { id: T, cycle: Vec<T> },
+}
+
+impl<T: Debug> Error for UpdateError<T> {}
+
+impl<T: Debug> fmt::Display for UpdateError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(f, "Node {id:?} is not present in the dag."),
+            Self::Cycle { id, cycle } => write!(f, "The new references of node {id:?} would close the cycle {cycle:?}."),
+        }
+    }
+}
+
+/// Errors returned when building a `CsrDag`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CsrError<T> {
+    /// The nodes are not consistent, as listed by the report.
+    Invalid(ValidationReport<T>),
+    /// The layout would have more nodes or edges than `u32` indices can address.
+    TooLarge { nodes: usize, edges: usize },
+}
+
+impl<T: Debug> Error for CsrError<T> {}
+
+impl<T: Debug> fmt::Display for CsrError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(report) => write!(f, "The nodes are not consistent: {report:?}."),
+            Self::TooLarge { nodes, edges } => write!(f, "{nodes} nodes and {edges} edges can't be addressed by u32 indices."),
+        }
+    }
+}
+
+/// Errors returned by `Analysis::count_linear_extensions`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CountError {
+    /// The number of orders exceeds `u128::MAX`.
+    Overflow,
+    /// The dag has more than `limit` ideals of one size, so counting them would exhaust time and memory, see `extensions::MAX_IDEALS`.
+    TooManyIdeals { limit: usize },
+}
+
+impl Error for CountError {}
+
+impl fmt::Display for CountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Overflow => write!(f, "The number of orders exceeds u128::MAX."),
+            Self::TooManyIdeals { limit } => write!(f, "The dag has more than {limit} ideals of one size."),
+        }
+    }
+}
+
+impl<T> From<ValidationReport<T>> for CsrError<T> {
+    fn from(report: ValidationReport<T>) -> Self {
+        Self::Invalid(report)
+    }
+}
+
 #[test]
+#[allow(unused_variables, clippy::from_str_radix_10)] // baseline test, kept as written.
 fn parse_int_error() {
         if let Err(error) = i32::from_str_radix("g12", 10) {
-            let topological_error : TopologicalError = error.into();
+            let topological_error : TopologicalError<u32> = error.into();
         };
-}
\ No newline at end of file
+}
+
+#[test]
+fn cycle_error_display() {
+    let error: TopologicalError<u32> = TopologicalError::Cycle { cycle: vec![1, 2], unordered: vec![1, 2, 3] };
+    assert_eq!(error.to_string(), "Provided list does not conform to a DAG, i.e. it has the cycle [1, 2] and 3 nodes could not be ordered.");
+}
@@ -0,0 +1,306 @@
+use core::fmt::Debug;
+use std::collections::HashMap;
+#[cfg(test)]
+use std::collections::HashSet;
+#[cfg(feature = "sampling")]
+use rand::Rng;
+use crate::{
+    Node,
+    DagNode,
+    error::CountError,
+    topological::Analysis,
+};
+
+/// Most ideals of one size `Analysis::count_linear_extensions` accepts, i.e. 2^20.
+/// It keeps the ideals of two consecutive sizes at once, each as a bitset of V bits with its count.
+pub const MAX_IDEALS: usize = 1 << 20;
+
+/// Adjacency of an analysis by position in its cached topological order, so every edge goes from a lower to a higher position.
+/// Parents referenced more than once count once, as they constrain the order once.
+#[derive(Debug, Clone)]
+struct Positions {
+    parents: Vec<Vec<usize>>,
+    children: Vec<Vec<usize>>,
+}
+
+impl Positions {
+    fn new<N: DagNode + Clone + Debug>(analysis: &Analysis<N>) -> Self {
+        let indices: HashMap<N::Id, usize> = analysis.order().iter().enumerate().map(|(position, id)| { (*id, position) }).collect();
+        let mut parents: Vec<Vec<usize>> = vec![Vec::new(); indices.len()];
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); indices.len()];
+        for (position, id) in analysis.order().iter().enumerate() {
+            let node = analysis.get(id).expect("Invalid topological assumptions."); // the order lists the analyzed nodes.
+            for ancestor in node.parents() {
+                let ancestor_position = indices[&ancestor];
+                if !parents[position].contains(&ancestor_position) {
+                    parents[position].push(ancestor_position);
+                    children[ancestor_position].push(position);
+                };
+            };
+        };
+        Positions { parents, children }
+    }
+    fn len(&self) -> usize {
+        self.parents.len()
+    }
+}
+
+/// Sets of positions as bitsets, 64 positions per word.
+type Ideal = Vec<u64>;
+
+fn contains(ideal: &Ideal, position: usize) -> bool {
+    ideal[position / 64] & (1 << (position % 64)) != 0
+}
+
+/// Counts the orders of the ideals one position larger than the given ones, i.e. of the ideals of the next size.
+/// Fails as soon as a count overflows a `u128` or there are more than `limit` ideals of the next size.
+fn next_ideal_counts(positions: &Positions, counts: &HashMap<Ideal, u128>, limit: usize) -> Result<HashMap<Ideal, u128>, CountError> {
+    let mut next_counts: HashMap<Ideal, u128> = HashMap::with_capacity(counts.len());
+    for (ideal, count) in counts {
+        for position in (0..positions.len()).filter(|position| { !contains(ideal, *position) }) {
+            if positions.parents[position].iter().all(|parent| { contains(ideal, *parent) }) {
+                let mut next_ideal = ideal.clone();
+                next_ideal[position / 64] |= 1 << (position % 64);
+                let next_count = next_counts.entry(next_ideal).or_insert(0);
+                *next_count = next_count.checked_add(*count).ok_or(CountError::Overflow)?;
+                if next_counts.len() > limit {
+                    return Err(CountError::TooManyIdeals { limit });
+                };
+            };
+        };
+    };
+    Ok(next_counts)
+}
+
+/// `LinearExtensions<'a, T>` lazily iterates over every topological order of an analysis, i.e. over its linear extensions, by backtracking.
+/// Each order is given once, the first one being the cached order of the analysis.
+/// Each order takes O(V² log V + E) time, as each of its V placements sorts the positions ready at that point.
+#[derive(Debug, Clone)]
+pub struct LinearExtensions<'a, T> {
+    ids: &'a [T],
+    positions: Positions,
+    in_degrees: Vec<usize>, // parents not yet placed.
+    placed: Vec<usize>,
+    frames: Vec<(Vec<usize>, usize)>, // positions ready to be placed at each depth, and the one chosen.
+    started: bool,
+}
+
+impl<'a, T: Copy> LinearExtensions<'a, T> {
+    fn place(&mut self, position: usize) {
+        self.placed.push(position);
+        for child in self.positions.children[position].iter() {
+            self.in_degrees[*child] -= 1;
+        };
+    }
+    fn unplace(&mut self, position: usize) {
+        assert_eq!(self.placed.pop(), Some(position));
+        for child in self.positions.children[position].iter() {
+            self.in_degrees[*child] += 1;
+        };
+    }
+    /// Places the first ready position until every position is placed.
+    fn descend(&mut self) {
+        while self.placed.len() < self.ids.len() {
+            let (candidates, chosen) = self.frames.last().expect("Invalid topological assumptions."); // descending follows a placement.
+            let chosen_position = candidates[*chosen];
+            let mut ready: Vec<usize> = candidates.iter().copied().filter(|position| { *position != chosen_position }).collect();
+            ready.extend(self.positions.children[chosen_position].iter().filter(|child| { self.in_degrees[**child] == 0 }));
+            ready.sort_unstable();
+            self.frames.push((ready, 0));
+            let (ready, _) = self.frames.last().expect("Invalid value assumption.");
+            self.place(ready[0]);
+        };
+    }
+}
+
+impl<T: Copy> Iterator for LinearExtensions<'_, T> {
+    type Item = Vec<T>;
+    fn next(&mut self) -> Option<Vec<T>> {
+        if !self.started {
+            self.started = true;
+            if self.ids.is_empty() {
+                return Some(Vec::new()); // the empty order is the only one.
+            };
+            let roots: Vec<usize> = (0..self.ids.len()).filter(|position| { self.in_degrees[*position] == 0 }).collect();
+            let first_root = roots[0];
+            self.frames.push((roots, 0));
+            self.place(first_root);
+        } else {
+            // Backtracks to the deepest frame with a candidate left, and places it.
+            loop {
+                let (current, has_next) = self.frames.last().map(|(candidates, chosen)| { (candidates[*chosen], *chosen + 1 < candidates.len()) })?;
+                self.unplace(current);
+                if has_next {
+                    let (candidates, chosen) = self.frames.last_mut().expect("Invalid value assumption.");
+                    *chosen += 1;
+                    let next = candidates[*chosen];
+                    self.place(next);
+                    break;
+                };
+                self.frames.pop();
+            };
+        };
+        self.descend();
+        Some(self.placed.iter().map(|position| { self.ids[*position] }).collect())
+    }
+}
+
+impl<N: DagNode + Clone + Debug> Analysis<N> {
+    /// Lazily iterates over every topological order of the analyzed nodes, see `LinearExtensions`.
+    pub fn linear_extensions(&self) -> LinearExtensions<'_, N::Id> {
+        let positions = Positions::new(self);
+        LinearExtensions {
+            ids: self.order(),
+            in_degrees: positions.parents.iter().map(|parents| { parents.len() }).collect(),
+            positions,
+            placed: Vec::new(),
+            frames: Vec::new(),
+            started: false,
+        }
+    }
+    /// Counts the topological orders of the analyzed nodes exactly.
+    /// The count is accumulated over the ideals of the dag, i.e. the sets of nodes closed under ancestors, size by size,
+    /// so it takes O(I · (V + E)) time for I ideals, and memory for the ideals of one size.
+    /// The number of ideals grows exponentially with the width of the dag, e.g. 20 independent nodes have 184756 ideals of size 10,
+    /// so it suits narrow dags: it fails with `CountError::TooManyIdeals` as soon as there are more than `MAX_IDEALS` ideals of one size,
+    /// and with `CountError::Overflow` if the count exceeds a `u128`, e.g. for two chains of 67 nodes.
+    pub fn count_linear_extensions(&self) -> Result<u128, CountError> {
+        let positions = Positions::new(self);
+        let mut counts: HashMap<Ideal, u128> = HashMap::from([(vec![0; positions.len().div_ceil(64)], 1)]); // orders of the ideals of the current size.
+        for _ in 0..positions.len() {
+            counts = next_ideal_counts(&positions, &counts, MAX_IDEALS)?;
+        };
+        Ok(counts.into_values().next().expect("Invalid topological assumptions.")) // the whole dag is its only ideal of full size.
+    }
+    /// Samples a topological order of the analyzed nodes within total variation distance `epsilon` of the uniform distribution,
+    /// or returns None if `epsilon` is not between 0 and 1, both excluded.
+    /// The order is the state of the Bubley–Dyer chain after enough steps, started from the cached order: each step picks
+    /// two neighbouring nodes, the pair ending at position p of the V - 1 pairs with probability proportional to p · (V - p),
+    /// and swaps them with probability 1/2 unless the first one is a parent of the second.
+    /// By path coupling the chain is within `epsilon` of uniform after (V³ - V) / 6 · ln(V (V - 1) / (2 · epsilon)) steps,
+    /// whatever the width of the dag, so sampling takes O(V³ log(V / epsilon) · (log V + D)) time for nodes of at most D parents, and O(V + E) memory.
+    #[cfg(feature = "sampling")]
+    pub fn sample_linear_extension<R: Rng + ?Sized>(&self, rng: &mut R, epsilon: f64) -> Option<Vec<N::Id>> {
+        if !(epsilon > 0.0 && epsilon < 1.0) {
+            return None;
+        };
+        let positions = Positions::new(self);
+        let length = positions.len();
+        if length < 2 {
+            return Some(self.order().to_vec()); // there is a single order.
+        };
+        let mut extension: Vec<usize> = (0..length).collect(); // the cached order, by position.
+        // ends[p - 1] is the total weight of the pairs ending at positions 1 to p.
+        let ends: Vec<u128> = (1..length as u128)
+            .scan(0, |total, end| {
+                *total += end * (length as u128 - end);
+                Some(*total)
+            })
+            .collect();
+        let total_weight = ends[ends.len() - 1]; // (V³ - V) / 6.
+        let diameter = (length * (length - 1) / 2) as f64; // most inversions between two orders.
+        let steps = (total_weight as f64 * (diameter / epsilon).ln()).ceil() as u64;
+        for _ in 0..steps {
+            let draw = rng.gen_range(0..total_weight);
+            let end = ends.partition_point(|weight| { *weight <= draw }) + 1;
+            if rng.gen_bool(0.5) && !positions.parents[extension[end]].contains(&extension[end - 1]) {
+                extension.swap(end - 1, end); // neighbouring nodes are incomparable unless one is a parent of the other.
+            };
+        };
+        Some(extension.into_iter().map(|position| { self.order()[position] }).collect())
+    }
+}
+
+#[test]
+fn linear_extensions_are_enumerated_and_counted() {
+    // A diamond with a detached node.
+    let node_list = [
+        Node::new(0, None, None, ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(2, Some(0), Some(0), ()), // references counting once.
+        Node::new(3, Some(1), Some(2), ()),
+        Node::new(4, None, None, ()),
+    ];
+    let Ok(Some(analysis)) = Analysis::new(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    let extensions: Vec<Vec<u32>> = analysis.linear_extensions().collect();
+    assert_eq!(extensions[0], analysis.order());
+    assert_eq!(extensions.len(), 10); // 2 orders of the diamond, and 5 places for the detached node.
+    assert_eq!(extensions.iter().collect::<HashSet<&Vec<u32>>>().len(), 10);
+    for extension in extensions.iter() {
+        for (position, id) in extension.iter().enumerate() {
+            assert!(analysis.get(id).expect("Invalid value assumption.").parents().all(|ancestor| { extension[..position].contains(&ancestor) }));
+        };
+    };
+    assert_eq!(analysis.count_linear_extensions(), Ok(10));
+    // Orders of two chains interleave in binomial(2m, m) ways.
+    let chains = |length: u32| {
+        let node_list: Vec<Node<u32, ()>> = (0..2 * length)
+            .map(|id| { Node::new(id, id.checked_sub(2), None, ()) })
+            .collect();
+        Analysis::new(&node_list).expect("Invalid topological assumptions for this test data.").expect("Invalid value assumption.")
+    };
+    assert_eq!(chains(4).linear_extensions().count(), 70);
+    assert_eq!(chains(4).count_linear_extensions(), Ok(70));
+    assert_eq!(chains(60).count_linear_extensions(), Ok(96_614_908_840_363_322_603_893_139_521_372_656));
+    assert_eq!(chains(67).count_linear_extensions(), Err(CountError::Overflow)); // binomial(134, 67) overflows a `u128`.
+    // 30 independent nodes have binomial(30, 7) = 2035800 ideals of size 7.
+    let antichain: Vec<Node<u32, ()>> = (0..30).map(|id| { Node::new(id, None, None, ()) }).collect();
+    let Ok(Some(analysis)) = Analysis::new(&antichain) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(analysis.count_linear_extensions(), Err(CountError::TooManyIdeals { limit: MAX_IDEALS }));
+    let Ok(Some(empty)) = Analysis::<Node<u32, ()>>::new(&[]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(empty.linear_extensions().collect::<Vec<Vec<u32>>>(), vec![Vec::<u32>::new()]);
+    assert_eq!(empty.count_linear_extensions(), Ok(1));
+}
+
+#[cfg(feature = "sampling")]
+#[test]
+fn linear_extensions_are_sampled_uniformly() {
+    use rand::SeedableRng;
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(7);
+    // 0 precedes 1 and 2 precedes 3 and 4, so 0 and 2 are not exchangeable: 20 orders.
+    let node_list = [
+        Node::new(0, None, None, ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(2, None, None, ()),
+        Node::new(3, Some(2), None, ()),
+        Node::new(4, Some(2), None, ()),
+    ];
+    let Ok(Some(analysis)) = Analysis::new(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    let extensions: HashSet<Vec<u32>> = analysis.linear_extensions().collect();
+    assert_eq!(extensions.len(), 20);
+    let samples: usize = 20_000;
+    let mut frequencies: HashMap<Vec<u32>, usize> = HashMap::new();
+    for _ in 0..samples {
+        *frequencies.entry(analysis.sample_linear_extension(&mut rng, 1e-4).expect("Invalid value assumption.")).or_default() += 1;
+    };
+    assert_eq!(frequencies.keys().cloned().collect::<HashSet<Vec<u32>>>(), extensions);
+    // Pearson's chi-squared statistic against the uniform distribution, below the 0.999 quantile for 19 degrees of freedom.
+    let expected = samples as f64 / 20.0;
+    let statistic: f64 = frequencies.values().map(|frequency| { (*frequency as f64 - expected).powi(2) / expected }).sum();
+    assert!(statistic < 43.8);
+    let chains: Vec<Node<u32, ()>> = (0..134_u32).map(|id| { Node::new(id, id.checked_sub(2), None, ()) }).collect();
+    let Ok(Some(analysis)) = Analysis::new(&chains) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    // binomial(134, 67) orders overflow a `u128`, but they are sampled all the same.
+    let extension = analysis.sample_linear_extension(&mut rng, 0.01).expect("Invalid value assumption.");
+    assert_eq!(extension.iter().collect::<HashSet<&u32>>().len(), 134);
+    for (position, id) in extension.iter().enumerate() {
+        assert!(analysis.get(id).expect("Invalid value assumption.").parents().all(|ancestor| { extension[..position].contains(&ancestor) }));
+    };
+    assert_ne!(extension, analysis.order()); // the chain moved away from the cached order.
+    assert_eq!(analysis.sample_linear_extension(&mut rng, 0.0), None);
+    assert_eq!(analysis.sample_linear_extension(&mut rng, 1.0), None);
+    let Ok(Some(empty)) = Analysis::<Node<u32, ()>>::new(&[]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(empty.sample_linear_extension(&mut rng, 0.01), Some(Vec::new()));
+}
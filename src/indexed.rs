@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+/// Indices of a layout that could not be sorted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Unsorted {
+    /// One of the cycles, in edge order, i.e. each index is a parent of the next one, and the last index is a parent of the first one.
+    pub cycle: Vec<usize>,
+    /// Every index that could not be placed, in ascending order.
+    pub unordered: Vec<usize>,
+}
+
+/// Sorts the indices `0..len` of a layout addressed by dense indices in a topological order with Kahn's algorithm, in O(V + E).
+/// `parents` and `children` give the indices referenced by and referencing each index, once per reference,
+/// and ready indices are placed in the order they were released, starting by the roots in ascending order.
+pub(crate) fn sort<P, I, C, J>(len: usize, parents: P, children: C) -> Result<Vec<usize>, Unsorted>
+where
+    P: Fn(usize) -> I,
+    I: Iterator<Item = usize>,
+    C: Fn(usize) -> J,
+    J: Iterator<Item = usize>,
+{
+    let mut in_degrees: Vec<usize> = (0..len).map(|index| { parents(index).count() }).collect();
+    let mut ready: VecDeque<usize> = (0..len).filter(|index| { in_degrees[*index] == 0 }).collect();
+    let mut ordering: Vec<usize> = Vec::with_capacity(len);
+    while let Some(index) = ready.pop_front() {
+        ordering.push(index);
+        for child in children(index) {
+            let in_degree = &mut in_degrees[child];
+            assert!(*in_degree > 0); // every edge is relaxed once, so the in-degree can't underflow.
+            *in_degree -= 1;
+            if *in_degree == 0 {
+                ready.push_back(child);
+            };
+        };
+    };
+    if ordering.len() == len {
+        Ok(ordering)
+    } else {
+        let unordered: Vec<usize> = (0..len).filter(|index| { in_degrees[*index] > 0 }).collect();
+        let cycle = find_cycle(unordered[0], &in_degrees, parents);
+        Err(Unsorted { cycle, unordered })
+    }
+}
+
+/// Walks the parents of an index that could not be ordered until an index repeats, and returns the cycle found in edge order.
+/// Every index left with a positive in-degree after sorting has at least one parent in the same condition, so the walk always closes a cycle.
+fn find_cycle<P, I>(start: usize, in_degrees: &[usize], parents: P) -> Vec<usize>
+where
+    P: Fn(usize) -> I,
+    I: Iterator<Item = usize>,
+{
+    let mut walk: Vec<usize> = Vec::new();
+    let mut walk_positions: Vec<Option<usize>> = vec![None; in_degrees.len()]; // position of each index in the walk.
+    let mut current = start;
+    let cycle_start = loop {
+        if let Some(walk_position) = walk_positions[current] {
+            break walk_position;
+        };
+        walk_positions[current] = Some(walk.len());
+        walk.push(current);
+        current = parents(current)
+            .find(|parent| { in_degrees[*parent] > 0 })
+            .expect("Invalid topological assumptions."); // unordered indices have at least one unordered parent.
+    };
+    let mut cycle = walk.split_off(cycle_start);
+    cycle.reverse(); // the walk follows parents, so it is reversed to follow the edges.
+    cycle
+}
+
+#[test]
+fn indices_are_sorted_or_their_cycle_reported() {
+    let parents: Vec<Vec<usize>> = vec![vec![], vec![0, 0], vec![1], vec![]];
+    let children: Vec<Vec<usize>> = vec![vec![1, 1], vec![2], vec![], vec![]];
+    let sorted = sort(4, |index| { parents[index].iter().copied() }, |index| { children[index].iter().copied() });
+    assert_eq!(sorted, Ok(vec![0, 3, 1, 2]));
+    let parents: Vec<Vec<usize>> = vec![vec![2], vec![0], vec![1], vec![], vec![2]];
+    let children: Vec<Vec<usize>> = vec![vec![1], vec![2], vec![0, 4], vec![], vec![]];
+    let unsorted = sort(5, |index| { parents[index].iter().copied() }, |index| { children[index].iter().copied() });
+    assert_eq!(unsorted, Err(Unsorted { cycle: vec![1, 2, 0], unordered: vec![0, 1, 2, 4] }));
+}
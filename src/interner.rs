@@ -0,0 +1,269 @@
+use core::{
+    hash::Hash,
+    fmt::Debug,
+};
+use std::collections::HashMap;
+use crate::{
+    NaryNode,
+    Dag,
+    error::TopologicalError,
+    topological::{
+        Topology,
+        PathLengths,
+    },
+};
+
+/// `Interner<K>` maps owned ids, e.g. `String` or `[u8; 32]` keys, to dense `u32` ids and back.
+/// Ids are given in order of first appearance, starting from 0.
+#[derive(Debug, Clone)]
+pub struct Interner<K: Eq + Hash + Clone> {
+    ids: HashMap<K, u32>,
+    keys: Vec<K>,
+}
+
+impl<K: Eq + Hash + Clone> Interner<K> {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Interner {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+        }
+    }
+    /// Returns the dense id of the key, interning it if it was not known.
+    pub fn intern(&mut self, key: &K) -> u32 {
+        if let Some(id) = self.ids.get(key) {
+            *id
+        } else {
+            let id = u32::try_from(self.keys.len()).expect("Interner capacity exceeded.");
+            self.keys.push(key.clone());
+            assert_eq!(self.ids.insert(key.clone(), id), None);
+            id
+        }
+    }
+    /// Gets the dense id of a key. Returns `None` if the key was never interned.
+    pub fn get(&self, key: &K) -> Option<u32> {
+        self.ids.get(key).copied()
+    }
+    /// Gets the key of a dense id. Returns `None` if the id was never given.
+    pub fn resolve(&self, id: u32) -> Option<&K> {
+        self.keys.get(id as usize)
+    }
+    /// Number of interned keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+    /// Returns `true` if no key has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+    /// Interns the id and parents of a keyed node and returns the equivalent node with dense ids.
+    pub fn intern_node<U: Clone>(&mut self, node: &KeyedNode<K, U>) -> NaryNode<u32, U> {
+        let id = self.intern(&node.id);
+        let parents = node.parents.iter().map(|parent| { self.intern(parent) }).collect();
+        NaryNode::new(id, parents, node.payload.clone())
+    }
+    /// Resolves a node with dense ids back to its keyed node.
+    pub fn resolve_node<U: Clone>(&self, node: &NaryNode<u32, U>) -> KeyedNode<K, U> {
+        KeyedNode::new(
+            self.resolve_id(node.id),
+            node.parents.iter().map(|parent| { self.resolve_id(*parent) }).collect(),
+            node.payload.clone(),
+        )
+    }
+    /// Resolves a list of dense ids back to their keys.
+    pub fn resolve_ids(&self, ids: &[u32]) -> Vec<K> {
+        ids.iter().map(|id| { self.resolve_id(*id) }).collect()
+    }
+    /// Resolves the ids carried by a topological error back to their keys.
+    pub fn resolve_error(&self, error: TopologicalError<u32>) -> TopologicalError<K> {
+        match error {
+            TopologicalError::Custom => TopologicalError::Custom,
+            TopologicalError::RepeatedNodes => TopologicalError::RepeatedNodes,
+            TopologicalError::InvalidTopologicalAssumptions => TopologicalError::InvalidTopologicalAssumptions,
+            TopologicalError::NotADag => TopologicalError::NotADag,
+            TopologicalError::FirstNodeHasIncomingEdges => TopologicalError::FirstNodeHasIncomingEdges,
+            TopologicalError::ParseIntError(e) => TopologicalError::ParseIntError(e),
+            TopologicalError::Cycle { cycle, unordered } => TopologicalError::Cycle {
+                cycle: self.resolve_ids(&cycle),
+                unordered: self.resolve_ids(&unordered),
+            },
+        }
+    }
+    fn resolve_id(&self, id: u32) -> K {
+        self.resolve(id).expect("Invalid interner assumption.").clone() // dense ids are only given by this interner.
+    }
+}
+
+impl<K: Eq + Hash + Clone> Default for Interner<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Node identified by an owned id, e.g. a `String`, referencing any number of ancestors by their ids.
+/// Keyed nodes are analyzed through an `Interner`, which maps them to `NaryNode<u32, U>`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyedNode<K, U> {
+    pub id: K,
+    pub parents: Vec<K>,
+    pub payload: U,
+}
+
+impl<K, U> KeyedNode<K, U> {
+    /// Creates a node referencing its ancestors by id in `parents`. An empty list means the node has no ancestors.
+    pub fn new(id: K, parents: Vec<K>, payload: U) -> Self {
+        KeyedNode {
+            id,
+            parents,
+            payload,
+        }
+    }
+}
+
+/// `Dag` over keyed nodes. Ids are interned on insertion, and lookups and results use the original ids.
+#[derive(Debug, Clone)]
+pub struct InternedDag<K: Eq + Hash + Clone, U: PartialEq> {
+    interner: Interner<K>,
+    dag: Dag<NaryNode<u32, U>>,
+}
+
+impl<K: Eq + Hash + Clone + Debug, U: Clone + PartialEq + Debug> InternedDag<K, U> {
+    /// Creates a new empty InternedDag marked as safe.
+    pub fn new() -> Self {
+        InternedDag {
+            interner: Interner::new(),
+            dag: Dag::new(),
+        }
+    }
+    /// Inserts a keyed node following the rules of `Dag::insert`, and returns the node reported by it with the original ids.
+    pub fn insert(&mut self, node: KeyedNode<K, U>) -> Option<KeyedNode<K, U>> {
+        let interned_node = self.interner.intern_node(&node);
+        self.dag
+            .insert(interned_node)
+            .map(|reported_node| { self.interner.resolve_node(&reported_node) })
+    }
+    /// Searches for nodes by their original id and returns `true` if present in the dag.
+    pub fn contains_id(&self, id: &K) -> bool {
+        self.interner.get(id).is_some_and(|interned_id| { self.dag.contains_id(&interned_id) })
+    }
+    /// Gets a node by its original id. Returns `Some(node)` if present, or `None` if not.
+    pub fn get(&self, id: &K) -> Option<KeyedNode<K, U>> {
+        let interned_id = self.interner.get(id)?;
+        self.dag.get(&interned_id).map(|node| { self.interner.resolve_node(node) })
+    }
+    /// Returns `true` if the underlying dag is safe, see `Dag::is_safe`.
+    pub fn is_safe(&self) -> bool {
+        self.dag.is_safe()
+    }
+    /// Interner mapping the original ids to the ids of the underlying dag.
+    pub fn interner(&self) -> &Interner<K> {
+        &self.interner
+    }
+    /// Underlying dag with dense ids.
+    pub fn dag(&self) -> &Dag<NaryNode<u32, U>> {
+        &self.dag
+    }
+}
+
+impl<K: Eq + Hash + Clone + Debug, U: Clone + PartialEq + Debug> Default for InternedDag<K, U> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `Topology` over keyed nodes. Ids are interned when the topology is built, and results use the original ids.
+#[derive(Debug, Clone)]
+pub struct InternedTopology<K: Eq + Hash + Clone, U: PartialEq> {
+    interner: Interner<K>,
+    topology: Topology<NaryNode<u32, U>>,
+}
+
+impl<K: Eq + Hash + Clone + Debug, U: Clone + PartialEq + Debug> InternedTopology<K, U> {
+    /// Constructs a topology from a slice of keyed nodes, as `Topology::from_slice` does.
+    pub fn from_slice(node_list: &[KeyedNode<K, U>]) -> Option<Self> {
+        let (interner, interned_nodes) = Self::intern_slice(node_list);
+        let topology = Topology::from_slice(&interned_nodes)?;
+        Some(InternedTopology {
+            interner,
+            topology,
+        })
+    }
+    /// Tries to build a topological sort from a list of keyed nodes, as `Topology::sort` does.
+    pub fn sort(nodes: &[KeyedNode<K, U>]) -> Result<Option<Vec<KeyedNode<K, U>>>, TopologicalError<K>> {
+        let (interner, interned_nodes) = Self::intern_slice(nodes);
+        match Topology::sort(&interned_nodes) {
+            Ok(ordering) => Ok(ordering.map(|ordering| {
+                ordering.iter().map(|node| { interner.resolve_node(node) }).collect()
+            })),
+            Err(error) => Err(interner.resolve_error(error)),
+        }
+    }
+    /// Calculates the shortest and longest paths from the *first* keyed node of the list, as `Topology::shortest_and_longest_paths` does.
+    pub fn shortest_and_longest_paths(nodes: &[KeyedNode<K, U>]) -> Result<Option<PathLengths<K>>, TopologicalError<K>> {
+        let (interner, interned_nodes) = Self::intern_slice(nodes);
+        match Topology::shortest_and_longest_paths(&interned_nodes) {
+            Ok(lengths) => Ok(lengths.map(|lengths| {
+                lengths.into_iter().map(|(id, length)| { (interner.resolve_id(id), length) }).collect()
+            })),
+            Err(error) => Err(interner.resolve_error(error)),
+        }
+    }
+    /// Evaluates all possible paths in the dag from a base node given by its original id, as `Topology::bfs_all_paths` does.
+    pub fn bfs_all_paths(&self, id: &K) -> Option<Vec<Vec<K>>> {
+        let interned_id = self.interner.get(id)?;
+        Topology::bfs_all_paths(&self.topology, interned_id)
+            .map(|paths| { paths.iter().map(|path| { self.interner.resolve_ids(path) }).collect() })
+    }
+    /// Interner mapping the original ids to the ids of the underlying topology.
+    pub fn interner(&self) -> &Interner<K> {
+        &self.interner
+    }
+    /// Underlying topology with dense ids.
+    pub fn topology(&self) -> &Topology<NaryNode<u32, U>> {
+        &self.topology
+    }
+    fn intern_slice(node_list: &[KeyedNode<K, U>]) -> (Interner<K>, Vec<NaryNode<u32, U>>) {
+        let mut interner = Interner::new();
+        let interned_nodes = node_list.iter().map(|node| { interner.intern_node(node) }).collect();
+        (interner, interned_nodes)
+    }
+}
+
+#[test]
+fn interner_gives_dense_ids_in_order() {
+    let mut interner = Interner::new();
+    assert_eq!(interner.intern(&String::from("main")), 0);
+    assert_eq!(interner.intern(&String::from("feature")), 1);
+    assert_eq!(interner.intern(&String::from("main")), 0);
+    assert_eq!(interner.len(), 2);
+    assert_eq!(interner.get(&String::from("feature")), Some(1));
+    assert_eq!(interner.get(&String::from("missing")), None);
+    assert_eq!(interner.resolve(1), Some(&String::from("feature")));
+    assert_eq!(interner.resolve(2), None);
+}
+
+#[test]
+fn interned_topological_order_reports_original_ids() {
+    let node_a = KeyedNode::new("serde", vec![], ());
+    let node_b = KeyedNode::new("serde_derive", vec!["serde"], ());
+    let node_c = KeyedNode::new("custom-dag", vec!["serde", "serde_derive"], ());
+    let node_list = [node_c.clone(), node_b.clone(), node_a.clone()];
+    let Ok(Some(ordering)) = InternedTopology::sort(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(ordering, vec![node_a.clone(), node_b.clone(), node_c.clone()]);
+    let Ok(Some(lengths)) = InternedTopology::shortest_and_longest_paths(&ordering) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(lengths.get("custom-dag"), Some(&(Some(1), Some(2))));
+    let Some(topology) = InternedTopology::from_slice(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    let Some(paths) = topology.bfs_all_paths(&"serde") else { panic!("Invalid topological assumptions for this test data.") };
+    assert!(paths.contains(&vec!["serde", "serde_derive", "custom-dag"]));
+    assert_eq!(topology.bfs_all_paths(&"rand"), None);
+}
+
+#[test]
+fn interned_cycle_reports_original_ids() {
+    let node_a = KeyedNode::new(String::from("a"), vec![String::from("b")], ());
+    let node_b = KeyedNode::new(String::from("b"), vec![String::from("a")], ());
+    let Err(TopologicalError::Cycle { cycle, unordered }) = InternedTopology::sort(&[node_a, node_b]) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(cycle.len(), 2);
+    assert!(cycle.contains(&String::from("a")) && cycle.contains(&String::from("b")));
+    assert_eq!(unordered, vec![String::from("a"), String::from("b")]);
+}
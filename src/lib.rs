@@ -31,12 +31,19 @@
 //! - dag-stats
 //! - wasm-binding
 
-use std::collections::{
-    HashMap,
-    HashSet,
+use std::{
+    collections::{
+        HashMap,
+        HashSet,
+        VecDeque,
+    },
+    time::Instant,
 };
 use core::{
-    hash::Hash,
+    hash::{
+        Hash,
+        Hasher,
+    },
     fmt::Debug
 };
 /// This module includes the code necessary for node collition analysis when manipulating the DAG structure.
@@ -44,32 +51,137 @@ pub mod collitions;
 /// This modules includes the helpers necessary for topological analysis of dag structure.
 #[allow(unused_imports)]
 pub mod topological;
-use collitions::CollidingNode;
+use topological::{
+    Topology,
+    PathLengths,
+};
+use collitions::{
+    CollidingNode,
+    CollitionPolicy,
+    CollitionDecision,
+    Resolution,
+};
 pub mod error;
-use error::TopologicalError;
+use error::{
+    TopologicalError,
+    RemovalError,
+    InsertError,
+    UpdateError,
+};
+/// This module includes the report of issues found when validating a list of nodes.
+pub mod validation;
+use validation::{
+    DanglingReference,
+    SafetyReport,
+};
+/// This module includes the interning layer that lets owned ids, e.g. `String`s, be used in the DAG analysis.
+pub mod interner;
+/// This module includes the evidence of nodes produced with the same id but different fields.
+pub mod equivocation;
+use equivocation::{
+    Equivocation,
+    FieldDiff,
+};
+/// This module includes a frozen, compressed-sparse-row layout of the DAG for large read-only analyses.
+pub mod csr;
+/// This module includes a borrowed, zero-copy topological view over a slice of nodes.
+pub mod view;
+/// This module includes the enumeration, counting and sampling of all the topological orders of a DAG.
+pub mod extensions;
+mod order;
+mod indexed;
+use order::OnlineOrder;
+/// This module includes the pool of nodes waiting for their references to be inserted in the DAG.
+pub mod pending;
+use pending::{
+    Attachment,
+    PendingLimits,
+    PendingPool,
+};
 
 use serde::{Serialize, Deserialize};  // Serde is called for wasm-bindgen implementation.
 
+/// Interface of the nodes analyzed by `Dag` and `Topology`.
+/// A node is identified by its id, references any number of ancestors by their ids and carries a payload.
+/// `Node` and `NaryNode` implement it, and so can any other type to be inserted and analyzed without conversion.
+/// Payloads take part in collition analysis only through the `payload_eq` and `payload_hash` hooks, so they need no particular bounds.
+/// ```
+/// use custom_dag::{
+///     DagNode,
+///     topological::Topology,
+/// };
+/// #[derive(Debug, Clone)]
+/// struct Package {
+///     id: u32,
+///     dependencies: Vec<u32>,
+///     version: (u8, u8),
+/// }
+/// impl DagNode for Package {
+///     type Id = u32;
+///     type Payload = (u8, u8);
+///     fn id(&self) -> u32 { self.id }
+///     fn parents(&self) -> impl Iterator<Item = u32> + '_ { self.dependencies.iter().copied() }
+///     fn payload(&self) -> &(u8, u8) { &self.version }
+/// }
+/// let core = Package { id: 0, dependencies: vec![], version: (1, 0) };
+/// let cli = Package { id: 1, dependencies: vec![0], version: (0, 3) };
+/// let ordering = Topology::sort(&[cli, core]).unwrap().unwrap();
+/// assert_eq!(ordering[0].payload(), &(1, 0));
+/// ```
+pub trait DagNode {
+    type Id: Eq + Hash + Copy + Debug;
+    type Payload;
+    /// Id of the node.
+    fn id(&self) -> Self::Id;
+    /// Ids of the ancestors referenced by the node, in order.
+    fn parents(&self) -> impl Iterator<Item = Self::Id> + '_;
+    /// Ids of the ancestors referenced by the node together with the branch referencing them.
+    fn references(&self) -> impl Iterator<Item = (Side, Self::Id)> + '_ {
+        self.parents().enumerate().map(|(position, id)| { (Side::Parent(position), id) })
+    }
+    /// Payload carried by the node.
+    fn payload(&self) -> &Self::Payload;
+    /// Compares the payloads of two nodes sharing an id when telling them apart as `CollidingNode`s.
+    /// Payloads are considered equal unless this hook is overridden.
+    fn payload_eq(&self, _other: &Self) -> bool {
+        true
+    }
+    /// Feeds the payload to the hasher of a `CollidingNode`. Payloads are not hashed unless this hook is overridden,
+    /// and an override must be consistent with `payload_eq`.
+    fn payload_hash<H: Hasher>(&self, _state: &mut H) {}
+    /// Counts the ancestors referenced by the node.
+    fn in_degree(&self) -> usize {
+        self.parents().count()
+    }
+}
+
 /// Custom Node struct.
 /// Nodes makes no assumption about the structure of the graph they are inserted in.
 #[derive(Serialize, Deserialize)]
-#[derive(Debug, Clone, Hash, Copy)]
-pub struct Node<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> {
+#[derive(Debug, Clone, Copy)]
+pub struct Node<T: Eq + Hash + PartialEq + Copy, U> {
     pub id: T,
     pub left: Option<T>,
     pub right: Option<T>,
     pub payload: U,
 }
 
-impl<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> PartialEq<Node<T, U>> for Node<T, U> {
+impl<T: Eq + Hash + PartialEq + Copy, U> PartialEq<Node<T, U>> for Node<T, U> {
     fn eq(&self, other: &Node<T, U>) -> bool {
         self.id == other.id
     }
 }
 
-impl<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> Eq for Node<T, U> {}
+impl<T: Eq + Hash + PartialEq + Copy, U> Eq for Node<T, U> {}
+
+/// Nodes are hashed by id, consistently with their equality.
+impl<T: Eq + Hash + PartialEq + Copy, U> Hash for Node<T, U> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
 
-impl<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> Node<T, U> {
+impl<T: Eq + Hash + PartialEq + Copy, U> Node<T, U> {
     /// The Node itself can be self-referential and makes no assumptions about the structure of the graph.
     /// Nodes define two ancestors by their id defined in left and right fields of same type as an option containing the id, i.e. `Some(id)`, of the node being referenced.
     /// If that branch does not references any node, the value can take the value `Option::<T>::None`.
@@ -98,97 +210,836 @@ impl<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> Node<T, U
     }
 }
 
+impl<T: Eq + Hash + PartialEq + Copy + Debug, U: PartialEq> DagNode for Node<T, U> {
+    type Id = T;
+    type Payload = U;
+    fn id(&self) -> T {
+        self.id
+    }
+    fn parents(&self) -> impl Iterator<Item = T> + '_ {
+        self.left.into_iter().chain(self.right)
+    }
+    fn references(&self) -> impl Iterator<Item = (Side, T)> + '_ {
+        self.left
+            .map(|id| { (Side::Left, id) })
+            .into_iter()
+            .chain(self.right.map(|id| { (Side::Right, id) }))
+    }
+    fn payload(&self) -> &U {
+        &self.payload
+    }
+    fn payload_eq(&self, other: &Self) -> bool {
+        self.payload == other.payload
+    }
+    fn in_degree(&self) -> usize {
+        Node::in_degree(self)
+    }
+}
+
+/// Node with an arbitrary number of ancestors.
+/// `NaryNode`s are equal if their id is equal, as `Node`s are.
+#[derive(Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+pub struct NaryNode<T: Eq + Hash + PartialEq + Copy, U> {
+    pub id: T,
+    pub parents: Vec<T>,
+    pub payload: U,
+}
+
+impl<T: Eq + Hash + PartialEq + Copy, U> PartialEq<NaryNode<T, U>> for NaryNode<T, U> {
+    fn eq(&self, other: &NaryNode<T, U>) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T: Eq + Hash + PartialEq + Copy, U> Eq for NaryNode<T, U> {}
+
+/// Nodes are hashed by id, consistently with their equality.
+impl<T: Eq + Hash + PartialEq + Copy, U> Hash for NaryNode<T, U> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T: Eq + Hash + PartialEq + Copy, U> NaryNode<T, U> {
+    /// Creates a node referencing its ancestors by id in `parents`. An empty list means the node has no ancestors.
+    pub fn new(id: T, parents: Vec<T>, payload: U) -> Self {
+        NaryNode {
+            id,
+            parents,
+            payload
+        }
+    }
+    /// Compares the equality of all fields from the base node to the reference of other node presented as the argument.
+    pub fn has_same_fields_to(&self, node: &NaryNode<T, U>) -> bool {
+        self.id == node.id
+        &&
+        self.parents == node.parents
+    }
+}
+
+impl<T: Eq + Hash + PartialEq + Copy, U> From<Node<T, U>> for NaryNode<T, U> {
+    fn from(node: Node<T, U>) -> Self {
+        NaryNode::new(node.id, node.left.into_iter().chain(node.right).collect(), node.payload)
+    }
+}
+
+impl<T: Eq + Hash + PartialEq + Copy + Debug, U: PartialEq> DagNode for NaryNode<T, U> {
+    type Id = T;
+    type Payload = U;
+    fn id(&self) -> T {
+        self.id
+    }
+    fn parents(&self) -> impl Iterator<Item = T> + '_ {
+        self.parents.iter().copied()
+    }
+    fn payload(&self) -> &U {
+        &self.payload
+    }
+    fn payload_eq(&self, other: &Self) -> bool {
+        self.payload == other.payload
+    }
+}
+
+/// Branch of a node referencing one of its ancestors.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Side {
+    Left,
+    Right,
+    /// Position of the ancestor in the parents list of a `NaryNode`.
+    Parent(usize),
+}
+
+/// Policy applied by `Dag::remove` to the nodes referencing the removed node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemovalPolicy {
+    /// Refuses to remove a node referenced by other nodes.
+    Refuse,
+    /// Removes the node and all of its descendants.
+    Cascade,
+    /// Removes the node only, leaving the nodes referencing it as orphans, i.e. with a reference to a missing node.
+    Orphan,
+}
+
+/// Outcome of `Dag::remove`.
+#[derive(Debug, Clone)]
+pub struct Removal<N: DagNode> {
+    /// Nodes removed from the dag, starting by the requested one.
+    pub removed: Vec<N>,
+    /// Ids of the nodes left referencing a removed node.
+    pub orphans: Vec<N::Id>,
+}
+
+/// Variant of a node that lost a collition, with the ids of the children that referenced it while it was stored.
+type ConflictingVariant<N> = (N, Vec<<N as DagNode>::Id>);
+
 /// Dag struct.
 #[derive(Debug, Clone)]
-pub struct Dag<T: Eq + Hash + PartialEq + Copy, U: Eq + Hash + PartialEq + Copy> {
-    nodes: HashMap<T, Node<T, U>>,
-    possible_collitions: HashMap<T, HashSet<CollidingNode<T, U>>>,
-    is_safe: bool,
+pub struct Dag<N: DagNode> {
+    nodes: HashMap<N::Id, N>,
+    children: HashMap<N::Id, Vec<N::Id>>, // reverse index of the references, i.e. the ids of the nodes referencing each id.
+    possible_collitions: HashMap<N::Id, HashSet<CollidingNode<N>>>,
+    collition_policy: CollitionPolicy<N>,
+    collition_decisions: Vec<CollitionDecision<N::Id>>,
+    audits_collitions: bool, // whether collition decisions are recorded.
+    conflicting_variants: HashMap<N::Id, Vec<ConflictingVariant<N>>>, // variants that lost a collition, in arrival order, with the children that referenced their id while they were stored.
+    unresolved: HashSet<N::Id>, // referenced ids that are not present in the dag.
+    overwritten: Vec<N::Id>,
+    cycles: HashMap<N::Id, Vec<N::Id>>, // a cycle through each node that closed one.
+    order: OnlineOrder<N::Id>,
+    deferred_edges: Vec<(N::Id, N::Id)>, // edges closing a cycle, left out of the order until the cycle is broken.
+    pending: PendingPool<N>,
 }
 
-impl<T: Eq + Hash + PartialEq + Copy + Debug, U: Eq + Hash + PartialEq + Copy + Debug> Dag<T, U> {
+impl<N: DagNode + Clone + Debug> Dag<N> {
     /// Creates a new empty Dag marked as safe.
     pub fn new() -> Self {
+        Self::with_pending_limits(PendingLimits::default())
+    }
+    /// Creates a new empty Dag marked as safe, whose pending pool is bounded by the given limits.
+    pub fn with_pending_limits(limits: PendingLimits) -> Self {
         Dag {
             nodes: HashMap::new(),
+            children: HashMap::new(),
             possible_collitions: HashMap::new(),
-            is_safe: true,
+            collition_policy: CollitionPolicy::KeepFirst,
+            collition_decisions: Vec::new(),
+            audits_collitions: true,
+            conflicting_variants: HashMap::new(),
+            unresolved: HashSet::new(),
+            overwritten: Vec::new(),
+            cycles: HashMap::new(),
+            order: OnlineOrder::new(),
+            deferred_edges: Vec::new(),
+            pending: PendingPool::new(limits),
         }
     }
     /// Check the safety of a given topological order creating a new Dag from a list of nodes given as a slice references of Nodes, i.e. `&[Node<T>]`.
     /// If the generation is succesful (i.e. conforms to a topological order for the nodes list) it returns `true`, otherwise `false`.
-    pub fn check_topological_order(node_list: &[Node<T, U>]) -> bool {
+    pub fn check_topological_order(node_list: &[N]) -> bool {
         let mut topology = Self::new();
-        topology.insert_from(node_list);
-        topology.is_safe()
+        node_list.iter().fold(true, |is_ordered, node| {
+            let references_are_present = node.parents().all(|ancestor| { topology.contains_id(&ancestor) });
+            topology.insert(node.clone());
+            is_ordered && references_are_present
+        })
     }
     /// Inserts nodes to the dag from a list.
-    pub fn insert_from(&mut self, node_list: &[Node<T, U>]) -> Vec<Option<Node<T, U>>> {
+    pub fn insert_from(&mut self, node_list: &[N]) -> Vec<Option<N>> {
         let node_iterator = node_list.iter();
         node_iterator.map(|node| { 
-            self.insert(*node)
+            self.insert(node.clone())
         }).collect()
     }
     /// Inserts a value only if the value doesn't exists, otherwise it collects it on a collition map.
     /// Note that as nodes only specifies ancestors and its id (but not descendants), if the node's id are not already included in the dag before the insertion
     /// but their references are or None, then it will always preserve the structure of a DAG.
     /// If the intented behaviour is updating an existing value, insert_or_update method should be used instead,
-    /// though this will create an unsafe condition for the acyclic structure of the DAG, and this will be marked in the is_safe (bool) field.
+    /// though this may break the acyclic structure of the DAG, which is reported by `safety_report`.
     /// If the id is not present in the dag but their references are, the node is inserted and None is returned.
-    /// If the id is not present in the dag and at least one of their reference is neither, it inserts the node in the dag but marks the is_safe flag as false and returns an option with the new value added to the dag.
-    /// If the id is present it does not update the dag, returns an option with the value that was present previously and accumulates the collition.
-    pub fn insert(&mut self, node: Node<T, U>) -> Option<Node<T, U>> {
-        if self.nodes.contains_key(&node.id) {
-            match self.possible_collitions.get_mut(&node.id) {
-                Some(collition_set) => { 
-                    collition_set.insert(node.into());
+    /// If the id is not present in the dag and at least one of their reference is neither, it inserts the node in the dag, which stays unsafe until the missing references are inserted, and returns an option with the new value added to the dag.
+    /// If the id is present it returns an option with the value that was present previously and resolves the collition with the dag's `CollitionPolicy`,
+    /// which by default does not update the dag and accumulates the collition. Resolutions are recorded in `collition_decisions` unless auditing is disabled, see `set_collition_audit`.
+    pub fn insert(&mut self, node: N) -> Option<N> {
+        let id = node.id();
+        if self.nodes.contains_key(&id) {
+            self.resolve_collition(node)
+        } else if node.parents().any(|ancestor| { !self.nodes.contains_key(&ancestor) }) {
+            self.attach(node, Instant::now());
+            self.nodes.get(&id).cloned()
+        }
+        else {
+            self.attach(node, Instant::now());
+            None
+        }
+    }
+    /// Strict version of `insert`: inserts the node only if its id is new and all of its references are present in the dag,
+    /// so the dag stays valid by construction and `is_safe` is never affected.
+    /// Otherwise the dag is left untouched, i.e. the node is neither inserted nor collected as a collition, and the reason is returned.
+    pub fn try_insert(&mut self, node: N) -> Result<(), InsertError<N::Id>> {
+        let id = node.id();
+        if let Some(present_node) = self.nodes.get(&id) {
+            return if CollidingNode::from(present_node.clone()).has_same_fields_to(&node) {
+                Err(InsertError::DuplicateId(id))
+            } else {
+                Err(InsertError::Collition(id))
+            };
+        };
+        if let Some((side, missing)) = node.references().find(|(_side, ancestor)| { !self.nodes.contains_key(ancestor) }) {
+            return Err(match side {
+                Side::Left => InsertError::MissingLeftParent { id, missing },
+                Side::Right => InsertError::MissingRightParent { id, missing },
+                Side::Parent(position) => InsertError::MissingParent { id, position, missing },
+            });
+        };
+        self.attach(node, Instant::now());
+        Ok(())
+    }
+    /// Inserts the node if its id is new and all of its references are present in the dag, otherwise holds it in the pending pool
+    /// until the missing references are inserted, so `is_safe` is never affected.
+    /// Inserting a node promotes the pending nodes it completes, recursively, whatever the insertion method used.
+    /// Pending nodes older than the pool's age limit are expired before holding or promoting nodes, and their ids are returned as evicted.
+    /// Ids already present in the dag or in the pending pool are rejected as `try_insert` does, and the node is dropped.
+    pub fn insert_or_hold(&mut self, node: N) -> Result<Attachment<N::Id>, InsertError<N::Id>> {
+        self.insert_or_hold_at(node, Instant::now())
+    }
+    /// Inserts or holds the node as `insert_or_hold` does, taking the given instant as the time of arrival.
+    pub fn insert_or_hold_at(&mut self, node: N, now: Instant) -> Result<Attachment<N::Id>, InsertError<N::Id>> {
+        let id = node.id();
+        if let Some(present_node) = self.nodes.get(&id).or_else(|| { self.pending.get(&id) }) {
+            return if CollidingNode::from(present_node.clone()).has_same_fields_to(&node) {
+                Err(InsertError::DuplicateId(id))
+            } else {
+                Err(InsertError::Collition(id))
+            };
+        };
+        let mut evicted: Vec<N::Id> = self.pending.expire(now).iter().map(|expired_node| { expired_node.id() }).collect();
+        let mut missing: Vec<N::Id> = Vec::new();
+        for ancestor in node.parents() {
+            if !self.nodes.contains_key(&ancestor) && !missing.contains(&ancestor) {
+                missing.push(ancestor);
+            };
+        };
+        if missing.is_empty() {
+            Ok(Attachment::Attached { attached: self.attach(node, now), evicted })
+        } else if self.pending.limits().max_nodes == Some(0) {
+            Ok(Attachment::Refused { missing, evicted })
+        } else {
+            evicted.extend(self.pending.hold(node, missing.clone(), now));
+            Ok(Attachment::Pending { missing, evicted })
+        }
+    }
+    /// Gets the pool of nodes waiting for their references.
+    pub fn pending(&self) -> &PendingPool<N> {
+        &self.pending
+    }
+    /// Evicts the pending nodes held for longer than the pool's age limit and returns them.
+    pub fn expire_pending(&mut self) -> Vec<N> {
+        self.expire_pending_at(Instant::now())
+    }
+    /// Evicts the pending nodes held for longer than the pool's age limit at the given instant and returns them.
+    pub fn expire_pending_at(&mut self, now: Instant) -> Vec<N> {
+        self.pending.expire(now)
+    }
+    /// Inserts a node with a new id, followed by the pending nodes it completes, and returns the inserted ids in insertion order.
+    /// Pending nodes older than the age limit at `now` are expired first, so they are not promoted.
+    /// A pending node whose id was inserted meanwhile by other means is resolved as a collition instead.
+    fn attach(&mut self, node: N, now: Instant) -> Vec<N::Id> {
+        self.pending.expire(now);
+        let mut attached = Vec::new();
+        let mut queue: VecDeque<N> = VecDeque::from([node]);
+        while let Some(next) = queue.pop_front() {
+            let id = next.id();
+            if self.nodes.contains_key(&id) {
+                self.resolve_collition(next);
+                continue;
+            };
+            assert!(self.place(next).is_none());
+            attached.push(id);
+            queue.extend(self.pending.release(&id));
+        };
+        attached
+    }
+    /// Applies the collition policy to a node whose id is already present in the dag, records the decision and returns the node that was present.
+    fn resolve_collition(&mut self, node: N) -> Option<N> {
+        let id = node.id();
+        let present_node = self.nodes.get(&id).cloned()?;
+        let is_duplicate = CollidingNode::from(present_node.clone()).has_same_fields_to(&node);
+        let replacement = match self.collition_policy.clone() {
+            CollitionPolicy::KeepFirst => None,
+            CollitionPolicy::KeepLast => Some((node.clone(), Resolution::KeptLast)),
+            CollitionPolicy::Reject => {
+                self.record_variant(node, is_duplicate);
+                self.record_decision(id, Resolution::Rejected);
+                return Some(present_node);
+            },
+            CollitionPolicy::Merge(merge) => {
+                let merged_node = merge(&present_node, &node);
+                if merged_node.id() != id {
+                    self.record_variant(node.clone(), is_duplicate);
+                    self.collect_collition(node);
+                    self.record_decision(id, Resolution::MergeRefused { merged_id: merged_node.id() });
+                    return Some(present_node);
+                };
+                Some((merged_node, Resolution::Merged))
+            },
+        };
+        let resolution = match replacement {
+            Some((replacement_node, resolution)) => match self.find_cycle_from(&id, replacement_node.parents()) {
+                Some(cycle) => {
+                    self.record_variant(node.clone(), is_duplicate);
+                    self.collect_collition(node);
+                    Resolution::CycleRefused { cycle }
                 },
                 None => {
-                    let mut collition_set = HashSet::new();
-                    assert!(collition_set.insert(CollidingNode::from(node)));
-                    assert_eq!(self.possible_collitions.insert(node.id, collition_set), None);
+                    for variant in [present_node.clone(), node] { // the merged node may differ from both.
+                        if !CollidingNode::from(variant.clone()).has_same_fields_to(&replacement_node) {
+                            self.record_variant(variant, is_duplicate);
+                        };
+                    };
+                    self.place(replacement_node);
+                    resolution
                 },
+            },
+            None => {
+                self.record_variant(node.clone(), is_duplicate);
+                self.collect_collition(node);
+                Resolution::KeptFirst
+            },
+        };
+        self.record_decision(id, resolution);
+        Some(present_node)
+    }
+    /// Keeps a variant that lost a collition as evidence of an equivocation, unless the newcomer was an exact duplicate of the present node.
+    /// A variant that was stored keeps the children referencing its id, as they built on it.
+    fn record_variant(&mut self, variant: N, is_duplicate: bool) {
+        if is_duplicate {
+            return;
+        };
+        let id = variant.id();
+        let is_stored = self.nodes.get(&id).is_some_and(|stored| { CollidingNode::from(stored.clone()).has_same_fields_to(&variant) });
+        let variants = self.conflicting_variants.entry(id).or_default();
+        let children: Vec<N::Id> = if is_stored {
+            let mut children: Vec<N::Id> = Vec::new();
+            for child in self.children.get(&id).into_iter().flatten() {
+                if !children.contains(child) && !variants.iter().any(|(_, built_children)| { built_children.contains(child) }) {
+                    children.push(*child);
+                };
             };
-            self.nodes.get(&node.id).copied()
-        } else if node.left.is_some()
-            && !self.nodes.contains_key(&node.left.expect("Invalid type definition assumption for Node<T>."))
-        {
-            self.is_safe = false;
-            assert_eq!(self.nodes.insert(node.id, node), None);
-            self.nodes.get(&node.id).copied()
-        } else if node.right.is_some() 
-            && !self.nodes.contains_key(&node.right.expect("Invalid type definition assumption for Node<T>."))
-        {
-            self.is_safe = false;
-            assert_eq!(self.nodes.insert(node.id, node), None);
-            self.nodes.get(&node.id).copied()
-        }
-        else {
-            assert_eq!(self.nodes.insert(node.id, node), None);
-            None
+            children
+        } else {
+            Vec::new()
+        };
+        match variants.iter_mut().find(|(known_variant, _)| { CollidingNode::from(known_variant.clone()).has_same_fields_to(&variant) }) {
+            Some((_, built_children)) => built_children.extend(children),
+            None => variants.push((variant, children)),
+        };
+    }
+    /// Records a collition decision if auditing is enabled, including those on exact duplicates of the present node, e.g. re-deliveries.
+    fn record_decision(&mut self, id: N::Id, resolution: Resolution<N::Id>) {
+        if self.audits_collitions {
+            self.collition_decisions.push(CollitionDecision { id, resolution });
+        };
+    }
+    /// Sets the policy applied by `insert` to the nodes whose id is already present.
+    pub fn set_collition_policy(&mut self, policy: CollitionPolicy<N>) {
+        self.collition_policy = policy;
+    }
+    /// Gets the policy applied by `insert` to the nodes whose id is already present.
+    pub fn collition_policy(&self) -> &CollitionPolicy<N> {
+        &self.collition_policy
+    }
+    /// Enables or disables the record of collition decisions, which is enabled by default.
+    /// The record grows with every collition, re-deliveries included, so long-lived dags should drain it with `take_collition_decisions`.
+    pub fn set_collition_audit(&mut self, enabled: bool) {
+        self.audits_collitions = enabled;
+    }
+    /// Gets the decisions taken on collitions while auditing was enabled, in the order they were taken.
+    pub fn collition_decisions(&self) -> &[CollitionDecision<N::Id>] {
+        &self.collition_decisions
+    }
+    /// Takes the decisions taken on collitions so far, leaving the record empty.
+    pub fn take_collition_decisions(&mut self) -> Vec<CollitionDecision<N::Id>> {
+        std::mem::take(&mut self.collition_decisions)
+    }
+    /// Accumulates a node whose id is already present in the dag.
+    fn collect_collition(&mut self, node: N) {
+        match self.possible_collitions.get_mut(&node.id()) {
+            Some(collition_set) => { 
+                collition_set.insert(node.into());
+            },
+            None => {
+                let id = node.id();
+                let mut collition_set = HashSet::new();
+                assert!(collition_set.insert(CollidingNode::from(node)));
+                assert_eq!(self.possible_collitions.insert(id, collition_set), None);
+            },
+        };
+    }
+    /// This method updates a node if it already exists, and returns the previous node.
+    /// If this method is used effectively, the id is listed as overwritten in the safety report.
+    /// Nodes whose references would close a cycle are rejected, leaving the dag untouched.
+    pub fn insert_or_update(&mut self, node: N) -> Result<Option<N>, InsertError<N::Id>> {
+        let id = node.id();
+        if let Some(cycle) = self.find_cycle_from(&id, node.parents()) {
+            return Err(InsertError::Cycle { id, cycle });
+        };
+        let previous = self.place(node);
+        match &previous {
+            Some(_) => {
+                if !self.overwritten.contains(&id) {
+                    self.overwritten.push(id);
+                };
+            },
+            None => {
+                self.pending.expire(Instant::now()); // stale nodes are not promoted.
+                let promoted: Vec<N> = self.pending.release(&id);
+                for pending_node in promoted {
+                    self.attach(pending_node, Instant::now());
+                };
+            },
+        };
+        Ok(previous)
+    }
+    /// Removes a node by id, applying the selected policy to the nodes referencing it.
+    /// Collitions collected for the removed ids are dropped with them.
+    /// Leaving orphans marks the dag as unsafe, as inserting a node with a missing reference does.
+    pub fn remove(&mut self, id: &N::Id, policy: RemovalPolicy) -> Result<Removal<N>, RemovalError<N::Id>> {
+        if !self.nodes.contains_key(id) {
+            return Err(RemovalError::NotFound(*id));
+        };
+        let mut removal = Removal {
+            removed: Vec::new(),
+            orphans: Vec::new(),
+        };
+        match policy {
+            RemovalPolicy::Refuse => {
+                let children: Vec<N::Id> = self.children(id).iter().filter(|child| { *child != id }).copied().collect(); // a self reference doesn't count.
+                if !children.is_empty() {
+                    return Err(RemovalError::HasDescendants { id: *id, children });
+                };
+                removal.removed.push(self.detach(id));
+            },
+            RemovalPolicy::Cascade => {
+                let mut visited: HashSet<N::Id> = HashSet::from([*id]);
+                let mut queue: VecDeque<N::Id> = VecDeque::from([*id]);
+                while let Some(next) = queue.pop_front() {
+                    for child in self.children(&next) {
+                        if visited.insert(*child) {
+                            queue.push_back(*child);
+                        };
+                    };
+                    removal.removed.push(self.detach(&next));
+                };
+            },
+            RemovalPolicy::Orphan => {
+                let mut orphans: HashSet<N::Id> = HashSet::new();
+                removal.orphans = self.children(id)
+                    .iter()
+                    .filter(|child| { *child != id && orphans.insert(**child) })
+                    .copied()
+                    .collect();
+                removal.removed.push(self.detach(id));
+            },
+        };
+        self.recheck_cycles();
+        Ok(removal)
+    }
+    /// Takes a node out of the dag with its references and collitions.
+    fn detach(&mut self, id: &N::Id) -> N {
+        let node = self.nodes.remove(id).expect("Invalid value assumption."); // callers check the presence of the id.
+        self.unlink_children(&node);
+        self.possible_collitions.remove(id);
+        self.conflicting_variants.remove(id);
+        self.overwritten.retain(|overwritten_id| { overwritten_id != id });
+        self.order.remove(id);
+        self.deferred_edges.retain(|(parent, child)| { parent != id && child != id });
+        if self.children.contains_key(id) {
+            self.unresolved.insert(*id);
+        };
+        node
+    }
+    /// Replaces a present node with a node of the same id and new references, unless they would close a cycle, and returns the previous node.
+    fn replace_parents(&mut self, node: N) -> Result<N, UpdateError<N::Id>> {
+        let id = node.id();
+        if let Some(cycle) = self.find_cycle_from(&id, node.parents()) {
+            return Err(UpdateError::Cycle { id, cycle });
+        };
+        Ok(self.place(node).expect("Invalid value assumption.")) // callers check the presence of the id.
+    }
+    /// Puts a node in the dag, replacing the node with the same id if any, and keeps the safety state up to date.
+    /// Only the placed node can close a new cycle, as it is the only one whose edges are new.
+    fn place(&mut self, node: N) -> Option<N> {
+        let id = node.id();
+        self.link_children(&node);
+        let previous = self.nodes.insert(id, node);
+        self.unresolved.remove(&id);
+        let new_edges: Vec<(N::Id, N::Id)> = match &previous {
+            Some(previous_node) => {
+                self.unlink_children(previous_node);
+                self.recheck_cycles();
+                self.nodes[&id].parents().map(|ancestor| { (ancestor, id) }).collect()
+            },
+            None => {
+                self.order.push(id);
+                let mut children: Vec<N::Id> = self.children(&id).to_vec();
+                children.dedup();
+                children.into_iter().map(|child| { (id, child) }).collect()
+            },
+        };
+        for (parent, child) in new_edges {
+            self.order_edge(parent, child);
+        };
+        if self.children.contains_key(&id) {
+            match self.find_cycle_through(&id) {
+                Some(cycle) => self.cycles.insert(id, cycle),
+                None => self.cycles.remove(&id),
+            };
+        };
+        previous
+    }
+    /// Searches for a cycle through the given id, following the references of the nodes present in the dag,
+    /// and returns it in edge order starting by the id.
+    fn find_cycle_through(&self, id: &N::Id) -> Option<Vec<N::Id>> {
+        self.find_cycle_from(id, self.nodes.get(id)?.parents())
+    }
+    /// Searches for a cycle through the given id as if it referenced the given parents instead of its current references.
+    fn find_cycle_from(&self, id: &N::Id, parents: impl Iterator<Item = N::Id>) -> Option<Vec<N::Id>> {
+        let mut successors: HashMap<N::Id, N::Id> = HashMap::new(); // the node each ancestor was reached from.
+        let mut frontier: Vec<(N::Id, N::Id)> = parents.map(|ancestor| { (ancestor, *id) }).collect();
+        let mut queue: VecDeque<N::Id> = VecDeque::new();
+        loop {
+            for (ancestor, next) in frontier.drain(..) {
+                if ancestor == *id {
+                    let mut cycle = vec![*id];
+                    let mut current = next;
+                    while current != *id {
+                        cycle.push(current);
+                        current = successors[&current];
+                    };
+                    return Some(cycle);
+                };
+                if self.nodes.contains_key(&ancestor) && !successors.contains_key(&ancestor) {
+                    successors.insert(ancestor, next);
+                    queue.push_back(ancestor);
+                };
+            };
+            let next = queue.pop_front()?;
+            frontier.extend(self.nodes[&next].parents().map(|ancestor| { (ancestor, next) }));
         }
     }
-    /// This method updates a node if it already exists.
-    /// If this method is used effectively, the DAG will be marked as unsafe in its `is_safe` field as a `false` value.
-    pub fn insert_or_update(&mut self, node: Node<T, U>) -> Option<Node<T, U>> {
-        self.is_safe = false;
-        self.nodes.insert(node.id, node)
+    /// Drops the recorded cycles broken by a removal or an overwrite, looking for another cycle through the same node first.
+    fn recheck_cycles(&mut self) {
+        let broken: Vec<N::Id> = self.cycles
+            .iter()
+            .filter(|(_id, cycle)| {
+                cycle.iter().zip(cycle.iter().cycle().skip(1)).any(|(parent, child)| {
+                    !self.nodes.get(child).is_some_and(|node| { node.parents().any(|ancestor| { ancestor == *parent }) })
+                })
+            })
+            .map(|(id, _cycle)| { *id })
+            .collect();
+        for id in broken {
+            match self.find_cycle_through(&id) {
+                Some(cycle) => self.cycles.insert(id, cycle),
+                None => self.cycles.remove(&id),
+            };
+        };
+        for (parent, child) in std::mem::take(&mut self.deferred_edges) {
+            if self.nodes.get(&child).is_some_and(|node| { node.parents().any(|ancestor| { ancestor == parent }) }) {
+                self.order_edge(parent, child);
+            };
+        };
+    }
+    /// Repairs the maintained order for a new edge, deferring the edge if it closes a cycle.
+    fn order_edge(&mut self, parent: N::Id, child: N::Id) {
+        let nodes = &self.nodes;
+        let children = &self.children;
+        let is_ordered = self.order.add_edge(
+            parent,
+            child,
+            |id| { nodes.get(id).map(|node| { node.parents().collect::<Vec<N::Id>>() }).unwrap_or_default() },
+            |id| { children.get(id).cloned().unwrap_or_default() },
+        );
+        if !is_ordered {
+            self.deferred_edges.push((parent, child));
+        };
+    }
+    /// Gets the ids of the nodes in a topological order, maintained as nodes are inserted, updated or removed.
+    /// While the dag has cycles (see `safety_report`), the edges closing them are left out of the order.
+    pub fn order(&self) -> Vec<N::Id> {
+        self.order.iter().copied().collect()
+    }
+    /// Returns `true` if both nodes are present and `first` comes before `second` in `order`.
+    /// In particular, every node precedes its descendants while the dag has no cycles.
+    pub fn precedes(&self, first: &N::Id, second: &N::Id) -> bool {
+        self.order.precedes(first, second)
+    }
+    /// Adds the node's id to the children of each of its references.
+    fn link_children(&mut self, node: &N) {
+        for ancestor in node.parents() {
+            if !self.nodes.contains_key(&ancestor) {
+                self.unresolved.insert(ancestor);
+            };
+            self.children.entry(ancestor).or_default().push(node.id());
+        };
+    }
+    /// Removes the node's id from the children of each of its references.
+    fn unlink_children(&mut self, node: &N) {
+        for ancestor in node.parents() {
+            if let Some(children) = self.children.get_mut(&ancestor) {
+                if let Some(position) = children.iter().position(|child| { *child == node.id() }) {
+                    children.swap_remove(position);
+                };
+                if children.is_empty() {
+                    self.children.remove(&ancestor);
+                    self.unresolved.remove(&ancestor);
+                };
+            };
+        };
+    }
+    /// Iterates over the nodes present in the dag, following `order`.
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.order.iter().map(|id| { &self.nodes[id] })
+    }
+    /// Number of nodes present in the dag.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns `true` if no node is present in the dag.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Sorts the nodes present in the dag as `Topology::sort` does.
+    pub fn sort(&self) -> Result<Option<Vec<N>>, TopologicalError<N::Id>> {
+        Topology::sort(&self.nodes().cloned().collect::<Vec<N>>())
+    }
+    /// Calculates the shortest and longest paths from the given node to the nodes present in the dag, as `Topology::shortest_and_longest_paths` does.
+    /// Returns `Ok(None)` if the node is not present, and a `FirstNodeHasIncomingEdges` error if it is not a root.
+    pub fn shortest_and_longest_paths(&self, source: &N::Id) -> Result<Option<PathLengths<N::Id>>, TopologicalError<N::Id>> {
+        let Some(source_node) = self.nodes.get(source) else {
+            return Ok(None);
+        };
+        let mut node_list: Vec<N> = Vec::with_capacity(self.nodes.len());
+        node_list.push(source_node.clone());
+        node_list.extend(self.nodes().filter(|node| { node.id() != *source }).cloned());
+        Topology::shortest_and_longest_paths(&node_list)
+    }
+    /// Evaluates all possible paths starting at the given node, as `Topology::bfs_all_paths` does.
+    pub fn bfs_all_paths(&self, source: &N::Id) -> Option<Vec<Vec<N::Id>>> {
+        Topology::bfs_all_paths(&Topology::from(self), *source)
     }
     /// Searches for nodes by id and returns `true` if present in the nodes list.
-    pub fn contains_id(&self, id: &T) -> bool {
+    pub fn contains_id(&self, id: &N::Id) -> bool {
         self.nodes.contains_key(id)
     }
     /// Gets a node by id. Returns `Some(id)` if present, or `None` if not.
-    pub fn get(&self, id: &T) -> Option<&Node<T, U>> {
+    pub fn get(&self, id: &N::Id) -> Option<&N> {
         self.nodes.get(id)
     }
+    /// Gets the ids of the nodes referencing the given id, once per reference.
+    /// The id itself doesn't need to be in the dag, so nodes waiting for a missing ancestor are listed too.
+    pub fn children(&self, id: &N::Id) -> &[N::Id] {
+        self.children.get(id).map_or(&[], |children| { children.as_slice() })
+    }
+    /// Gets the ids referenced by a node. Returns `None` if the node is not present.
+    pub fn parents(&self, id: &N::Id) -> Option<Vec<N::Id>> {
+        self.nodes.get(id).map(|node| { node.parents().collect() })
+    }
+    /// Counts the references to the given id, i.e. the edges going out of it.
+    pub fn out_degree(&self, id: &N::Id) -> usize {
+        self.children(id).len()
+    }
+    /// Returns `true` if the node is present and no node references it.
+    pub fn is_tip(&self, id: &N::Id) -> bool {
+        self.nodes.contains_key(id) && self.out_degree(id) == 0
+    }
+    /// Gets the ids of the nodes in the dag descending from the given id, in breadth-first order.
+    pub fn descendants(&self, id: &N::Id) -> Vec<N::Id> {
+        let mut visited: HashSet<N::Id> = HashSet::from([*id]);
+        let mut queue: VecDeque<N::Id> = VecDeque::from([*id]);
+        let mut descendants = Vec::new();
+        while let Some(next) = queue.pop_front() {
+            for child in self.children(&next) {
+                if self.nodes.contains_key(child) && visited.insert(*child) {
+                    descendants.push(*child);
+                    queue.push_back(*child);
+                };
+            };
+        };
+        descendants
+    }
+    /// Gets the ids of the given nodes and of their descendants present in the dag, in breadth-first order.
+    fn descendants_of(&self, roots: &[N::Id]) -> Vec<N::Id> {
+        let mut visited: HashSet<N::Id> = HashSet::new();
+        let mut queue: VecDeque<N::Id> = roots
+            .iter()
+            .filter(|root| { self.nodes.contains_key(*root) && visited.insert(**root) })
+            .copied()
+            .collect();
+        let mut descendants: Vec<N::Id> = queue.iter().copied().collect();
+        while let Some(next) = queue.pop_front() {
+            for child in self.children(&next) {
+                if self.nodes.contains_key(child) && visited.insert(*child) {
+                    descendants.push(*child);
+                    queue.push_back(*child);
+                };
+            };
+        };
+        descendants
+    }
+    /// Lists every variant that lost a collition and differs from the node stored with the same id, whatever the collition policy,
+    /// with the field-level differences and the descendants building on each variant.
+    /// Equivocations are sorted by id and then by diff, and equal diffs keep the order the variants arrived in.
+    pub fn equivocations(&self) -> Vec<Equivocation<N::Id, N>>
+    where
+        N::Id: Ord,
+    {
+        let mut equivocations = Vec::new();
+        for (id, variants) in self.conflicting_variants.iter() {
+            let Some(stored) = self.nodes.get(id) else {
+                continue;
+            };
+            let conflicting_variants: Vec<_> = variants
+                .iter()
+                .map(|(conflicting, built_children)| { (conflicting, built_children, FieldDiff::between(stored, conflicting)) })
+                .filter(|(_, _, diff)| { !diff.is_empty() }) // a variant stored again is no longer conflicting.
+                .collect();
+            let stored_children: Vec<N::Id> = self
+                .children(id)
+                .iter()
+                .filter(|child| { !conflicting_variants.iter().any(|(_, built_children, _)| { built_children.contains(*child) }) })
+                .copied()
+                .collect();
+            let stored_descendants = self.descendants_of(&stored_children);
+            for (conflicting, built_children, diff) in conflicting_variants {
+                equivocations.push(Equivocation {
+                    id: *id,
+                    stored: stored.clone(),
+                    conflicting: conflicting.clone(),
+                    diff,
+                    stored_descendants: stored_descendants.clone(),
+                    conflicting_descendants: self.descendants_of(built_children),
+                });
+            };
+        };
+        equivocations.sort_by(|left, right| { (left.id, &left.diff).cmp(&(right.id, &right.diff)) }); // the sort is stable.
+        equivocations
+    }
     /// Returns an Option with a reference of the a HashSet of possible collitions for the list of nodes inserted.
-    pub fn get_collitions(&self, id: &T) -> Option<&HashSet<CollidingNode<T, U>>> {
+    pub fn get_collitions(&self, id: &N::Id) -> Option<&HashSet<CollidingNode<N>>> {
         self.possible_collitions.get(id)
     }
-    /// Gets the value of the dag safety marker.
-    pub fn is_safe(&mut self) -> bool {
-        self.is_safe
+    /// Returns `true` if no node references a missing id and no cycle was closed, see `safety_report`.
+    pub fn is_safe(&self) -> bool {
+        self.unresolved.is_empty() && self.cycles.is_empty()
+    }
+    /// Reports the unresolved references, the overwritten ids and the cycles of the dag.
+    pub fn safety_report(&self) -> SafetyReport<N::Id> {
+        let mut report = SafetyReport::new();
+        for missing in self.unresolved.iter() {
+            let mut visited: HashSet<N::Id> = HashSet::new();
+            for child in self.children(missing) {
+                if !visited.insert(*child) {
+                    continue;
+                };
+                let node = self.nodes.get(child).expect("Invalid value assumption."); // the children index only lists present nodes.
+                report.unresolved_references.extend(node
+                    .references()
+                    .filter(|(_side, ancestor)| { ancestor == missing })
+                    .map(|(side, _ancestor)| { DanglingReference { id: *child, side, missing: *missing } })
+                );
+            };
+        };
+        report.overwritten_ids = self.overwritten.clone();
+        for cycle in self.cycles.values() {
+            let is_listed = report.cycles.iter().any(|listed: &Vec<N::Id>| {
+                listed.len() == cycle.len()
+                && (0..cycle.len()).any(|offset| { listed.iter().eq(cycle[offset..].iter().chain(cycle[..offset].iter())) })
+            });
+            if !is_listed { // the same cycle may be closed through several nodes.
+                report.cycles.push(cycle.clone());
+            };
+        };
+        report
+    }
+}
+
+impl<T: Eq + Hash + PartialEq + Copy + Debug, U: PartialEq + Clone + Debug> Dag<Node<T, U>> {
+    /// Replaces the references of a node, and returns the previous ones as a `(left, right)` pair.
+    /// The change is rejected with the cycle it would close if the node is reachable from the new references.
+    /// As with `insert`, references to missing nodes make the dag unsafe until they are inserted.
+    pub fn set_parents(&mut self, id: &T, left: Option<T>, right: Option<T>) -> Result<(Option<T>, Option<T>), UpdateError<T>> {
+        let node = self.nodes.get(id).ok_or(UpdateError::NotFound(*id))?;
+        let updated_node = Node::new(*id, left, right, node.payload.clone());
+        self.replace_parents(updated_node).map(|previous_node| { (previous_node.left, previous_node.right) })
+    }
+    /// Replaces the payload of a node and returns the previous one. The references and the safety of the dag are not affected.
+    pub fn set_payload(&mut self, id: &T, payload: U) -> Result<U, UpdateError<T>> {
+        let node = self.nodes.get_mut(id).ok_or(UpdateError::NotFound(*id))?;
+        Ok(std::mem::replace(&mut node.payload, payload))
+    }
+}
+
+impl<T: Eq + Hash + PartialEq + Copy + Debug, U: PartialEq + Clone + Debug> Dag<NaryNode<T, U>> {
+    /// Replaces the references of a node, and returns the previous ones.
+    /// The change is rejected with the cycle it would close if the node is reachable from the new references.
+    /// As with `insert`, references to missing nodes make the dag unsafe until they are inserted.
+    pub fn set_parents(&mut self, id: &T, parents: Vec<T>) -> Result<Vec<T>, UpdateError<T>> {
+        let node = self.nodes.get(id).ok_or(UpdateError::NotFound(*id))?;
+        let updated_node = NaryNode::new(*id, parents, node.payload.clone());
+        self.replace_parents(updated_node).map(|previous_node| { previous_node.parents })
+    }
+    /// Replaces the payload of a node and returns the previous one. The references and the safety of the dag are not affected.
+    pub fn set_payload(&mut self, id: &T, payload: U) -> Result<U, UpdateError<T>> {
+        let node = self.nodes.get_mut(id).ok_or(UpdateError::NotFound(*id))?;
+        Ok(std::mem::replace(&mut node.payload, payload))
+    }
+}
+
+impl<N: DagNode + Clone + Debug> Default for Dag<N> {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
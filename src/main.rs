@@ -4,11 +4,11 @@ use std::{
 };
 use custom_dag::{
     Node,
-    topological::Topology,
+    topological::Analysis,
     error::TopologicalError,
 };
 
-fn main() -> Result<(), TopologicalError> {
+fn main() -> Result<(), TopologicalError<u32>> {
     let args: Vec<String> = env::args().collect();
     let file_path = &args[1];
 
@@ -22,66 +22,59 @@ fn main() -> Result<(), TopologicalError> {
 
     nodes_list.push(root); // Adds the first node to the nodes_list
     for (i,node_data) in enumerated_lines {
-        match node_data.split_once(" ") {
-            Some((left, right)) => {
-                nodes_list.push(Node::new((i + 1) as u32, Some(left.parse().expect("Invalid file format.")), Some(right.parse()?), ()));
-            },
-            None => {}
+        if let Some((left, right)) = node_data.split_once(" ") {
+            nodes_list.push(Node::new((i + 1) as u32, Some(left.parse().expect("Invalid file format.")), Some(right.parse()?), ()));
         };
         
     }
-    //let Some(sorted) = Topology::sort(&nodes_list)? else { panic!("Invalid topological assumptions for this test data.") };
-    match Topology::sort(&nodes_list)? {
-        Some(sorted) => {
-            let Ok(Some(mut shortest_and_longest)) = Topology::shortest_and_longest_paths(&sorted) else { panic!("Invalid topological assumptions for this test data.") };
-            let Some(topology) = Topology::from_slice(&nodes_list)  else { panic!("Invalid topological assumptions for this test data.") };
-            let Some(bfs_all_paths) = Topology::bfs_all_paths(&topology, root.id) else { panic!("Invalid topological assumptions for this test data.") };
-            let all_paths_size_sum: usize = bfs_all_paths.iter().map(|path| { path.len() }).sum();
-            let average_node_size = all_paths_size_sum as f32/bfs_all_paths.len() as f32;
-        
-            // println!("Shortest and longest path sizes : {:?}", shortest_and_longest);
-        
-            let outdegree = topology
-                .outgoing_edges
-                .iter()
-                .map(|(_id, edges)| {
-                    edges.len()
-                });
-            let _outdegree_nodes = outdegree.len();
-            let _outdegree_sum: usize = outdegree.sum();
-        
-            let aggregation = shortest_and_longest
-                .iter_mut()
-                .map(|(_id, (left, right))| {
-                    (left, right)
-                })
-                .fold((
-                    (0_usize), // Number of nodes with shortest depth 0.
-                    (0_usize, 0_usize), // sum aggregation of shortest path lengths in the the dag.
-                    (0_usize,0_usize) // sum aggregation of longest path lengths in the dag..
-                ), |mut acc, (shortest, longest)| {
-                    if let Some(value) = shortest {
-                            if value == &0 {
-                                acc.0 += 1;
-                            }
-                            acc.2.0 += *value ;
-                            acc.1.0 += 1;
-                    };
-                    if let Some(value) = longest { 
-                            acc.2.1 += *value;
-                            acc.1.1 += 1;
-                    };
-                    acc
-                });
-        
-                let average_shortest_depth = aggregation.2.0 as f32 / aggregation.1.0 as f32;
-                let average_longest_depth = aggregation.2.1 as f32 / aggregation.1.1 as f32;
-                println!("AVG SHORT DAG DEPTH: {average_shortest_depth}");
-                println!("AVG LONG DAG DEPTH: {average_longest_depth}");
-                println!("NUMBER OF ALL PATHS {}", bfs_all_paths.len());
-                println!("AVG NODES PER PATH {average_node_size}");
-        },
-        None => {}
+    if let Some(analysis) = Analysis::new(&nodes_list)? { // the topology and its order are built once for all the analyses.
+        let mut shortest_and_longest = analysis.shortest_and_longest_paths(&root.id).ok_or(TopologicalError::InvalidTopologicalAssumptions)?;
+        let bfs_all_paths = analysis.bfs_all_paths(&root.id);
+        let all_paths_size_sum: usize = bfs_all_paths.iter().map(|path| { path.len() }).sum();
+        let average_node_size = all_paths_size_sum as f32/bfs_all_paths.len() as f32;
+
+        // println!("Shortest and longest path sizes : {:?}", shortest_and_longest);
+
+        let outdegree = analysis
+            .topology()
+            .outgoing_edges
+            .values()
+            .map(|edges| {
+                edges.len()
+            });
+        let _outdegree_nodes = outdegree.len();
+        let _outdegree_sum: usize = outdegree.sum();
+
+        let aggregation = shortest_and_longest
+            .iter_mut()
+            .map(|(_id, (left, right))| {
+                (left, right)
+            })
+            .fold((
+                (0_usize), // Number of nodes with shortest depth 0.
+                (0_usize, 0_usize), // sum aggregation of shortest path lengths in the the dag.
+                (0_usize,0_usize) // sum aggregation of longest path lengths in the dag..
+            ), |mut acc, (shortest, longest)| {
+                if let Some(value) = shortest {
+                    if value == &0 {
+                        acc.0 += 1;
+                    }
+                    acc.2.0 += *value ;
+                    acc.1.0 += 1;
+                };
+                if let Some(value) = longest { 
+                    acc.2.1 += *value;
+                    acc.1.1 += 1;
+                };
+                acc
+            });
+
+        let average_shortest_depth = aggregation.2.0 as f32 / aggregation.1.0 as f32;
+        let average_longest_depth = aggregation.2.1 as f32 / aggregation.1.1 as f32;
+        println!("AVG SHORT DAG DEPTH: {average_shortest_depth}");
+        println!("AVG LONG DAG DEPTH: {average_longest_depth}");
+        println!("NUMBER OF ALL PATHS {}", bfs_all_paths.len());
+        println!("AVG NODES PER PATH {average_node_size}");
     }
 
     // println!("Outdegree nodes {outdegree_nodes} {outdegree_sum}");
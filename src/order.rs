@@ -0,0 +1,102 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+    HashSet,
+};
+use core::hash::Hash;
+
+/// `OnlineOrder<T>` keeps a topological order of a changing graph, repairing it locally on each new edge as in the
+/// Pearce–Kelly algorithm, i.e. only the nodes between the edge's endpoints in the current order are visited and shifted.
+#[derive(Debug, Clone)]
+pub(crate) struct OnlineOrder<T> {
+    positions: HashMap<T, u64>,
+    ids: BTreeMap<u64, T>,
+    next_position: u64,
+}
+
+impl<T: Eq + Hash + Copy> OnlineOrder<T> {
+    pub(crate) fn new() -> Self {
+        OnlineOrder {
+            positions: HashMap::new(),
+            ids: BTreeMap::new(),
+            next_position: 0,
+        }
+    }
+    /// Places an id after every other id.
+    pub(crate) fn push(&mut self, id: T) {
+        assert!(self.positions.insert(id, self.next_position).is_none());
+        self.ids.insert(self.next_position, id);
+        self.next_position += 1;
+    }
+    pub(crate) fn remove(&mut self, id: &T) {
+        if let Some(position) = self.positions.remove(id) {
+            self.ids.remove(&position);
+        };
+    }
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> {
+        self.ids.values()
+    }
+    /// Returns `true` if both ids are ordered and `first` comes before `second`.
+    pub(crate) fn precedes(&self, first: &T, second: &T) -> bool {
+        match (self.positions.get(first), self.positions.get(second)) {
+            (Some(first_position), Some(second_position)) => first_position < second_position,
+            _ => false,
+        }
+    }
+    /// Repairs the order after adding an edge from `parent` to `child`. `parents_of` and `children_of` give the ordered neighbours of an id,
+    /// the new edge included. Returns `false`, leaving the order untouched, if the edge closes a cycle.
+    pub(crate) fn add_edge<P, PI, C, CI>(&mut self, parent: T, child: T, parents_of: P, children_of: C) -> bool
+    where
+        P: Fn(&T) -> PI,
+        PI: IntoIterator<Item = T>,
+        C: Fn(&T) -> CI,
+        CI: IntoIterator<Item = T>,
+    {
+        if parent == child {
+            return false;
+        };
+        let (Some(&upper_bound), Some(&lower_bound)) = (self.positions.get(&parent), self.positions.get(&child)) else {
+            return true; // edges to ids out of the order don't constrain it.
+        };
+        if upper_bound < lower_bound {
+            return true;
+        };
+        // Nodes reachable from the child that are placed up to the parent.
+        let mut forward: Vec<T> = Vec::new();
+        let mut visited: HashSet<T> = HashSet::from([child]);
+        let mut stack: Vec<T> = vec![child];
+        while let Some(next) = stack.pop() {
+            forward.push(next);
+            for descendant in children_of(&next) {
+                if descendant == parent {
+                    return false;
+                };
+                if self.positions.get(&descendant).is_some_and(|position| { *position < upper_bound }) && visited.insert(descendant) {
+                    stack.push(descendant);
+                };
+            };
+        };
+        // Nodes reaching the parent that are placed from the child on.
+        let mut backward: Vec<T> = Vec::new();
+        let mut visited: HashSet<T> = HashSet::from([parent]);
+        let mut stack: Vec<T> = vec![parent];
+        while let Some(next) = stack.pop() {
+            backward.push(next);
+            for ancestor in parents_of(&next) {
+                if self.positions.get(&ancestor).is_some_and(|position| { *position > lower_bound }) && visited.insert(ancestor) {
+                    stack.push(ancestor);
+                };
+            };
+        };
+        // The backward nodes take the lowest of the affected positions, keeping the relative order within each group.
+        backward.sort_by_key(|id| { self.positions[id] });
+        forward.sort_by_key(|id| { self.positions[id] });
+        let mut positions: Vec<u64> = backward.iter().chain(forward.iter()).map(|id| { self.positions[id] }).collect();
+        positions.sort_unstable();
+        for (id, position) in backward.into_iter().chain(forward).zip(positions) {
+            self.positions.insert(id, position);
+            self.ids.insert(position, id);
+        };
+        true
+    }
+}
@@ -0,0 +1,177 @@
+use std::{
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use crate::DagNode;
+
+/// Limits of the pending pool of a `Dag`. `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PendingLimits {
+    /// Maximum number of nodes held. The oldest nodes are evicted first when it is exceeded, and no node is held with `Some(0)`.
+    pub max_nodes: Option<usize>,
+    /// Maximum time a node is held. Older nodes are evicted when nodes are held or promoted, or by `Dag::expire_pending`.
+    pub max_age: Option<Duration>,
+}
+
+/// Outcome of `Dag::insert_or_hold`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Attachment<T> {
+    /// The node was inserted, followed by the pending nodes it completed, in insertion order.
+    /// `evicted` lists the ids of the pending nodes dropped to respect the pool age limit, as in `Pending`.
+    Attached { attached: Vec<T>, evicted: Vec<T> },
+    /// The node is held until all the `missing` ids are inserted.
+    /// `evicted` lists the ids of the pending nodes dropped to respect the pool limits, i.e. those older than the age limit and then the oldest ones beyond the size limit.
+    Pending { missing: Vec<T>, evicted: Vec<T> },
+    /// The node misses the `missing` ids but was dropped, as the pool size limit is `Some(0)`.
+    /// `evicted` lists the ids of the pending nodes dropped to respect the pool age limit, as in `Pending`.
+    Refused { missing: Vec<T>, evicted: Vec<T> },
+}
+
+/// Node held in the pending pool.
+#[derive(Debug, Clone)]
+struct PendingNode<N> {
+    node: N,
+    missing: usize, // number of distinct ids the node is still waiting for.
+    sequence: u64,
+    received: Instant,
+}
+
+/// `PendingPool<N>` holds the nodes whose references are not present in the dag yet, indexed by the ids they are waiting for.
+#[derive(Debug, Clone)]
+pub struct PendingPool<N: DagNode> {
+    nodes: HashMap<N::Id, PendingNode<N>>,
+    waiting_on: HashMap<N::Id, Vec<N::Id>>, // ids of the pending nodes waiting for each missing id.
+    arrivals: VecDeque<(u64, N::Id)>, // arrival order, used for eviction. Entries of nodes that already left the pool are skipped.
+    sequence: u64,
+    limits: PendingLimits,
+}
+
+impl<N: DagNode> PendingPool<N> {
+    /// Creates an empty pool with the given limits.
+    pub fn new(limits: PendingLimits) -> Self {
+        PendingPool {
+            nodes: HashMap::new(),
+            waiting_on: HashMap::new(),
+            arrivals: VecDeque::new(),
+            sequence: 0,
+            limits,
+        }
+    }
+    /// Limits of the pool.
+    pub fn limits(&self) -> PendingLimits {
+        self.limits
+    }
+    /// Number of nodes held.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    /// Returns `true` if no node is held.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    /// Returns `true` if a node with this id is held.
+    pub fn contains_id(&self, id: &N::Id) -> bool {
+        self.nodes.contains_key(id)
+    }
+    /// Gets a held node by id.
+    pub fn get(&self, id: &N::Id) -> Option<&N> {
+        self.nodes.get(id).map(|pending_node| { &pending_node.node })
+    }
+    /// Iterates over the held nodes.
+    pub fn waiting_nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.values().map(|pending_node| { &pending_node.node })
+    }
+    /// Ids that held nodes are waiting for.
+    pub fn missing_ids(&self) -> Vec<N::Id> {
+        self.waiting_on.keys().copied().collect()
+    }
+    /// Ids of the held nodes waiting for the given id.
+    pub fn waiting_for(&self, id: &N::Id) -> &[N::Id] {
+        self.waiting_on.get(id).map_or(&[], |waiting| { waiting.as_slice() })
+    }
+    /// Holds a node until all the `missing` ids arrive, and returns the ids evicted to respect the size limit.
+    pub(crate) fn hold(&mut self, node: N, missing: Vec<N::Id>, now: Instant) -> Vec<N::Id> {
+        let id = node.id();
+        for missing_id in missing.iter() {
+            self.waiting_on.entry(*missing_id).or_default().push(id);
+        };
+        self.sequence += 1;
+        self.arrivals.push_back((self.sequence, id));
+        assert!(self.nodes.insert(id, PendingNode { node, missing: missing.len(), sequence: self.sequence, received: now }).is_none());
+        let mut evicted = Vec::new();
+        if let Some(max_nodes) = self.limits.max_nodes {
+            while self.nodes.len() > max_nodes {
+                match self.evict_oldest() {
+                    Some(node) => evicted.push(node.id()),
+                    None => break,
+                };
+            };
+        };
+        evicted
+    }
+    /// Marks the id as arrived, and takes out of the pool the nodes that were only waiting for it.
+    pub(crate) fn release(&mut self, id: &N::Id) -> Vec<N> {
+        let mut released = Vec::new();
+        for waiting_id in self.waiting_on.remove(id).unwrap_or_default() {
+            let pending_node = self.nodes.get_mut(&waiting_id).expect("Invalid value assumption."); // waiting ids are removed with their nodes.
+            pending_node.missing -= 1;
+            if pending_node.missing == 0 {
+                released.push(self.nodes.remove(&waiting_id).expect("Invalid value assumption.").node);
+            };
+        };
+        released
+    }
+    /// Evicts the nodes held for longer than the age limit at the given instant.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<N> {
+        let mut expired = Vec::new();
+        if let Some(max_age) = self.limits.max_age {
+            while let Some((sequence, id)) = self.arrivals.front().copied() {
+                match self.nodes.get(&id) {
+                    Some(pending_node) if pending_node.sequence == sequence => {
+                        if now.saturating_duration_since(pending_node.received) < max_age {
+                            break;
+                        };
+                        expired.push(self.evict(&id));
+                    },
+                    _ => { // the node already left the pool.
+                        self.arrivals.pop_front();
+                    },
+                };
+            };
+        };
+        expired
+    }
+    fn evict_oldest(&mut self) -> Option<N> {
+        while let Some((sequence, id)) = self.arrivals.pop_front() {
+            if self.nodes.get(&id).is_some_and(|pending_node| { pending_node.sequence == sequence }) {
+                return Some(self.evict(&id));
+            };
+        };
+        None
+    }
+    /// Takes a node out of the pool, removing it from the lists of the ids it was waiting for.
+    fn evict(&mut self, id: &N::Id) -> N {
+        let pending_node = self.nodes.remove(id).expect("Invalid value assumption."); // callers check the presence of the id.
+        for parent in pending_node.node.parents() {
+            if let Some(waiting) = self.waiting_on.get_mut(&parent) {
+                waiting.retain(|waiting_id| { waiting_id != id });
+                if waiting.is_empty() {
+                    self.waiting_on.remove(&parent);
+                };
+            };
+        };
+        pending_node.node
+    }
+}
+
+impl<N: DagNode> Default for PendingPool<N> {
+    fn default() -> Self {
+        Self::new(PendingLimits::default())
+    }
+}
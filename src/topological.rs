@@ -1,113 +1,175 @@
 use core::{
     hash::Hash,
+    fmt::Debug,
+    ops::Add,
+    cmp::{
+        Ordering as CmpOrdering,
+        Reverse,
+    },
 };
 use std::{
     collections::{
+        BinaryHeap,
         HashSet,
         HashMap,
+        VecDeque,
     },
     fmt,
 };
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use crate::{
     Node,
+    NaryNode,
+    DagNode,
+    Side,
     collitions::CollidingNode,
     Dag,
     error::TopologicalError,
+    validation::{
+        self,
+        DanglingReference,
+        ValidationReport,
+    },
 };
 
+/// Shortest and longest path length of a node, `None` when the node is unreachable.
+pub type PathLength = (Option<usize>, Option<usize>);
+/// Shortest and longest path lengths by node id, as computed by `Topology::shortest_and_longest_paths`.
+pub type PathLengths<T> = HashMap<T, PathLength>;
+/// Shortest and longest path weight of a node, `None` when the node is unreachable.
+pub type WeightedPathLength<W> = (Option<W>, Option<W>);
+/// Shortest and longest path weights by node id, as computed by `Analysis::weighted_shortest_and_longest_paths`.
+pub type WeightedPathLengths<T, W> = HashMap<T, WeightedPathLength<W>>;
+/// Nodes sorted in levels, as computed by `Topology::sort_levels`.
+/// The level of a node is the length of the longest path reaching it from a root, so the nodes of a level don't reference each other
+/// and can be processed concurrently once the previous levels are done.
+#[derive(Debug, Clone)]
+pub struct Levels<N: DagNode> {
+    /// Nodes of each level, in the order they were released.
+    pub levels: Vec<Vec<N>>,
+    /// Level of each node, by id.
+    pub level_indices: HashMap<N::Id, usize>,
+}
+
+impl<N: DagNode> Levels<N> {
+    /// Gets the level of the node with the given id.
+    pub fn level_of(&self, id: &N::Id) -> Option<usize> {
+        self.level_indices.get(id).copied()
+    }
+    /// Number of nodes of each level.
+    pub fn widths(&self) -> Vec<usize> {
+        self.levels.iter().map(|level| { level.len() }).collect()
+    }
+}
+
+/// Topological order of ids and the end of each of its levels in it, as computed by `Topology::topological_order`.
+type LeveledOrder<T> = (Vec<T>, Vec<usize>);
+
 /// Topology struct layout for analysis.
 #[derive(Debug, Clone)]
-pub struct Topology<T: Eq + Hash + PartialEq + Copy + std::fmt::Debug, U: Eq + Hash + PartialEq + Copy> {
-    all_nodes: HashSet<CollidingNode<T, U>>, // collection of all nodes compared by all fields
-    unique_nodes: HashMap<T, Node<T, U>>,
-    collitions: HashSet<CollidingNode<T, U>>,
-    repeated_nodes: HashMap<T, HashSet<CollidingNode<T, U>>>,
-    pub outgoing_edges: HashMap<T, Vec<T>>,
+pub struct Topology<N: DagNode> {
+    all_nodes: HashSet<CollidingNode<N>>, // collection of all nodes compared by all fields
+    unique_nodes: HashMap<N::Id, N>,
+    unique_ids: Vec<N::Id>, // ids of the unique nodes, in the order they were inserted.
+    collitions: HashSet<CollidingNode<N>>,
+    repeated_nodes: HashMap<N::Id, HashSet<CollidingNode<N>>>,
+    pub outgoing_edges: HashMap<N::Id, Vec<N::Id>>,
 }
 
 /// Implements Topology struct.
 #[allow(dead_code)]
-impl<T: Eq + Hash + PartialEq + Copy + std::fmt::Debug, U: Eq + Hash + PartialEq + Copy + std::fmt::Debug> Topology<T, U> {
+impl<N: DagNode + Clone + Debug> Topology<N> {
     /// New Topology layout.
-    fn new() -> Topology<T, U> {
+    fn new() -> Topology<N> {
         Topology {
             all_nodes: HashSet::new(),
             unique_nodes: HashMap::new(),
+            unique_ids: Vec::new(),
             collitions: HashSet::new(),
             repeated_nodes: HashMap::new(),
             outgoing_edges: HashMap::new(),
         }
     }
     /// Inserts the node into the topology analysis.
-    /// If it finds a collition, returns an Option<CollidingNode<T>> with the value of the node.
+    /// If it finds a collition, returns an Option<CollidingNode<N::Id>> with the value of the node.
     /// otherwise it returns None if there is no collision.
-    fn insert(&mut self, node: Node<T, U>) -> Option<CollidingNode<T, U>>{
-        if self.all_nodes.insert(node.into()) { // if the node didn't existed in the collection of all nodes (compared by all fields)
-            if self.get_unique_node_by_id(node.id).is_none() { // if the node didn't existed in the collection of unique nodes (compared by id)
-                assert_eq!(self.unique_nodes.insert(node.id, node), None); // Inserts node to the unique nodes collection.
-                self.collect_edges(node); // collect the node's edges
+    fn insert(&mut self, node: N) -> Option<CollidingNode<N>>{
+        if self.all_nodes.insert(node.clone().into()) { // if the node didn't existed in the collection of all nodes (compared by all fields)
+            if self.get_unique_node_by_id(node.id()).is_none() { // if the node didn't existed in the collection of unique nodes (compared by id)
+                self.collect_edges(&node); // collect the node's edges
+                self.unique_ids.push(node.id());
+                assert!(self.unique_nodes.insert(node.id(), node).is_none()); // Inserts node to the unique nodes collection.
                 None
             } else { // else if the node was already inserted in the collection of unique nodes (compared by id)
-                if self.collitions.insert(node.into()) { // insert the node to the collection of collitions; if the node was not listed there
+                if self.collitions.insert(node.clone().into()) { // insert the node to the collection of collitions; if the node was not listed there
                     // done
                 } else {
-                    self.collect_repeated_node(node); // also collect the node as a repeated_node
+                    self.collect_repeated_node(node.clone()); // also collect the node as a repeated_node
                 };
                 Some(node.into())  // return the colliding node.
             }
         } else { // else if the node existed in the collection of all nodes (compared by all fields)
-            assert_eq!(self.get_unique_node_by_id(node.id), Some(node));  // It should have been already added to the collection of unique nodes (compared by id).
-            self.collect_repeated_node(node); // and collects it to the collection of repeated nodes.
+            assert!(self.unique_nodes.contains_key(&node.id()));  // It should have been already added to the collection of unique nodes (compared by id).
+            self.collect_repeated_node(node.clone()); // and collects it to the collection of repeated nodes.
             Some(node.into()) // return the colliding node
         }
     }
+    /// Builds the topology of a list of nodes, as inserting them in order does.
+    fn from_nodes(node_list:&[N]) -> Self {
+        let mut topology = Topology::new();
+        for node in node_list {
+            topology.insert(node.clone());
+        };
+        topology
+    }
     /// Constructs a topology from a slice of nodes.
     /// If a consistent DAG topology can be constructed, 
     /// it returns and Option with the topology,
     /// otherwise it returns None.
-    pub fn from_slice(node_list:&[Node<T, U>]) -> Option<Self> {
-        let mut topology = Topology::new();
-        for node in node_list {
-            topology.insert(*node);
-        };
-        if topology.is_consistent() {
-            Some(topology)
+    pub fn from_slice(node_list:&[N]) -> Option<Self> {
+        Self::try_from_slice(node_list).ok()
+    }
+    /// Constructs a topology from a slice of nodes.
+    /// If a consistent DAG topology can be constructed, it returns the topology,
+    /// otherwise it returns the `ValidationReport` listing the issues found.
+    pub fn try_from_slice(node_list:&[N]) -> Result<Self, ValidationReport<N::Id>> {
+        Topology::from_nodes(node_list).validated()
+    }
+    /// Returns the topology if it is consistent, otherwise the `ValidationReport` listing the issues found.
+    fn validated(self) -> Result<Self, ValidationReport<N::Id>> {
+        let report = self.validate();
+        if report.is_consistent() {
+            Ok(self)
         } else {
-            None
+            Err(report)
         }
     }
-    fn collect_repeated_node(&mut self, node: Node<T, U>) {
-        if let Some(repeated_nodes_set) = self.repeated_nodes.get_mut(&node.id) { // if the set of repeated nodes is already created for this node.id
+    fn collect_repeated_node(&mut self, node: N) {
+        let id = node.id();
+        if let Some(repeated_nodes_set) = self.repeated_nodes.get_mut(&id) { // if the set of repeated nodes is already created for this node.id
             repeated_nodes_set.insert(node.into());  // insert the node into the collection of repeated nodes
         } else {  // else, if the set of repeated nodes have not been created yet for this node.id
-            let mut new_set: HashSet<CollidingNode<T, U>> = HashSet::new(); // creates a new set
+            let mut new_set: HashSet<CollidingNode<N>> = HashSet::new(); // creates a new set
             new_set.insert(node.into());  // inserts the node into the set
-            assert_eq!(self.repeated_nodes.insert(node.id, new_set), None); // and inserts the set into the collection of repeated nodes for this node.id
+            assert_eq!(self.repeated_nodes.insert(id, new_set), None); // and inserts the set into the collection of repeated nodes for this node.id
         };
     }
-    fn collect_edges(&mut self, node: Node<T, U>) {
-        match node.left {
-            None => {},
-            Some(ancestor) => {
-                if let Some(edges_for_ancestor) = self.outgoing_edges.get_mut(&ancestor) { // pushes the node.id to the list of its ancestor's directed edges (directed to it)
-                    edges_for_ancestor.push(node.id);  // Inserts the edge 
-                } else {
-                    assert_eq!(self.outgoing_edges.insert(ancestor, vec!(node.id)), None);  // Inserts the edge and asserts that the list didn't existed before.
-                }
-            },
-        };
-        match node.right {
-            None => {},
-            Some(ancestor) => {
-                if let Some(edges_for_ancestor) = self.outgoing_edges.get_mut(&ancestor) { // pushes the node.id to the list of its ancestor's directed edges (directed to it
-                    edges_for_ancestor.push(node.id); // Inserts the edge
-                } else {
-                    assert_eq!(self.outgoing_edges.insert(ancestor, vec!(node.id)), None); // Inserts the edge and asserts that the list didn't existed before.
-                }
-            },
+    fn collect_edges(&mut self, node: &N) {
+        for ancestor in node.parents() {
+            if let Some(edges_for_ancestor) = self.outgoing_edges.get_mut(&ancestor) { // pushes the node.id to the list of its ancestor's directed edges (directed to it)
+                edges_for_ancestor.push(node.id());  // Inserts the edge 
+            } else {
+                assert_eq!(self.outgoing_edges.insert(ancestor, vec!(node.id())), None);  // Inserts the edge and asserts that the list didn't existed before.
+            }
         };
     }
-    fn get_outgoing_edges_by_id(&self, id: T) -> Option<&Vec<T>> {
+    fn get_outgoing_edges_by_id(&self, id: N::Id) -> Option<&Vec<N::Id>> {
         self.outgoing_edges.get(&id)
     }
     fn edge_sum(&self) -> usize {
@@ -117,8 +179,8 @@ impl<T: Eq + Hash + PartialEq + Copy + std::fmt::Debug, U: Eq + Hash + PartialEq
             .map(|list| { list.len() })
             .sum()
     }
-    fn get_unique_node_by_id(&self, id:T) -> Option<Node<T, U>> {
-        self.unique_nodes.get(&id).copied()
+    fn get_unique_node_by_id(&self, id:N::Id) -> Option<N> {
+        self.unique_nodes.get(&id).cloned()
     }
     /// Checks the consistency of nodes with its references, 
     /// i.e. checks: 
@@ -131,161 +193,307 @@ impl<T: Eq + Hash + PartialEq + Copy + std::fmt::Debug, U: Eq + Hash + PartialEq
         self.repeated_nodes.is_empty()
         && self.collitions.is_empty()
         && self.unique_nodes
-            .iter()
-            .fold(true, |acc, node| {
-                acc &&
-                ( node.1.left.is_none() || self.get_unique_node_by_id(node.1.left.expect("Invalid value assumption")).is_some() ) &&
-                ( node.1.right.is_none() || self.get_unique_node_by_id(node.1.right.expect("Invalid value assumption")).is_some() )
+            .values()
+            .all(|node| {
+                node.parents().all(|ancestor| { self.unique_nodes.contains_key(&ancestor) })
             })
     }
+    /// Lists every issue of the nodes inserted in the topology, i.e.
+    /// dangling references, repeated ids, collitions, self-references and duplicate parents, each with the ids involved.
+    /// Issues follow the order in which the ids were first inserted, without repeated entries, so the same list always gives the same report.
+    pub fn validate(&self) -> ValidationReport<N::Id> {
+        let repeated_ids: HashSet<N::Id> = self.repeated_nodes.keys().copied().collect();
+        let colliding_ids: HashSet<N::Id> = self.collitions.iter().map(|colliding_node| { colliding_node.0.id() }).collect();
+        validation::report(
+            self.unique_ids.iter().map(|id| { &self.unique_nodes[id] }),
+            |id| { self.unique_nodes.contains_key(id) },
+            &repeated_ids,
+            &colliding_ids,
+        )
+    }
     /// Tries to build a topological sort from a list of nodes.
-    /// Returns a sequence of nodes that follows a topological order if it exists, otherwise it returns None.
-    pub fn sort(nodes:&[Node<T, U>]) -> Result<Option<Vec<Node<T, U>>>, TopologicalError > {
-        let mut topology: Topology<T, U> = Topology::new();
-        for node in nodes.iter() {
-            topology.insert(*node);
+    /// Returns a sequence of nodes that follows a topological order if it exists.
+    /// If the list is not consistent (see `is_consistent`) it returns None,
+    /// and if the list has a cycle it returns a `TopologicalError::Cycle` with one of its cycles and all the nodes that could not be ordered.
+    /// The sort follows Kahn's algorithm: ids with zero in-degree are kept in a ready queue and each edge is relaxed once,
+    /// so the whole ordering runs in O(V + E).
+    pub fn sort(nodes:&[N]) -> Result<Option<Vec<N>>, TopologicalError<N::Id> > {
+        Self::sort_with(nodes, Self::from_nodes, Self::topological_order)
+    }
+    /// Sorts as `sort` does, building the topology with `from_nodes` and ordering it with `topological_order`.
+    fn sort_with<F, O>(nodes:&[N], from_nodes: F, topological_order: O) -> Result<Option<Vec<N>>, TopologicalError<N::Id>>
+    where
+        F: FnOnce(&[N]) -> Self,
+        O: FnOnce(&Self, &[N]) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>>,
+    {
+        let topology: Topology<N> = from_nodes(nodes);
+        if !topology.is_consistent() {
+            return Ok(None);
+        };
+        topological_order(&topology, nodes)?
+            .0
+            .into_iter()
+            .map(|id| { topology.get_unique_node_by_id(id).ok_or(TopologicalError::InvalidTopologicalAssumptions) })
+            .collect::<Result<Vec<N>, TopologicalError<N::Id>>>()
+            .map(Some)
+    }
+    /// Tries to build a topological sort from a list of nodes as `sort` does, keeping the levels Kahn's algorithm processes:
+    /// the first level holds the roots, and each level holds the nodes whose last ancestor is in the previous one, see `Levels`.
+    pub fn sort_levels(nodes:&[N]) -> Result<Option<Levels<N>>, TopologicalError<N::Id>> {
+        Self::sort_levels_with(nodes, Self::from_nodes, Self::topological_order)
+    }
+    /// Sorts as `sort_levels` does, building the topology with `from_nodes` and ordering it with `topological_order`.
+    fn sort_levels_with<F, O>(nodes:&[N], from_nodes: F, topological_order: O) -> Result<Option<Levels<N>>, TopologicalError<N::Id>>
+    where
+        F: FnOnce(&[N]) -> Self,
+        O: FnOnce(&Self, &[N]) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>>,
+    {
+        let topology: Topology<N> = from_nodes(nodes);
+        if !topology.is_consistent() {
+            return Ok(None);
         };
-        let checked_topology = if topology.collitions.is_empty()
-            && topology.repeated_nodes.is_empty()
-            && topology.is_consistent()
-            { Some(&topology) } else { None };
-        match checked_topology {
-            Some(topology_for_sorting) => { // sorting algorithm
-                let mut in_degree_map: HashMap<&T,usize> =
-                    topology_for_sorting
-                        .unique_nodes
-                        .iter()
-                        .map( |node| {
-                            (node.0, node.1.in_degree())
-                        })
-                        .collect();
-                let mut ordering: Vec<Node<T, U>> = Vec::with_capacity(in_degree_map.len());
-                let ordering = loop {
-                    let next_ordering: Vec<T> = in_degree_map
-                        .clone()
-                        .into_iter()
-                        .filter( |(_id,in_degree)| { 
-                            in_degree == &0_usize 
-                        })
-                        .map( |(id, _degree)| {
-                            *id
-                        })
-                        .collect();
-                    if !next_ordering.is_empty() {
-                        for id in next_ordering.iter() {
-                            ordering.push(topology_for_sorting.get_unique_node_by_id(*id).ok_or(TopologicalError::InvalidTopologicalAssumptions)?);
-                            match topology_for_sorting.outgoing_edges.get(id) {
-                                Some(edges) => {
-                                    assert!(!edges.is_empty()); // if it was inserted, should have values.
-                                    for outgoing_node_id in edges {
-                                        let in_degree = in_degree_map.get_mut(outgoing_node_id).ok_or(TopologicalError::InvalidTopologicalAssumptions)?;
-                                        assert!( in_degree > &mut 0 );
-                                        *in_degree -= 1;
-                                    };
-                                    Some(edges)
-                                },
-                                None => None
-                            };
-                            assert_eq!(in_degree_map.remove(id), Some(0_usize));
-                        }
-                    } else { break ordering; };
+        let (topological_order, level_ends) = topological_order(&topology, nodes)?;
+        let mut levels: Vec<Vec<N>> = Vec::with_capacity(level_ends.len());
+        let mut level_indices: HashMap<N::Id, usize> = HashMap::with_capacity(topological_order.len());
+        let mut level_start = 0;
+        for (level_index, level_end) in level_ends.into_iter().enumerate() {
+            let level = &topological_order[level_start..level_end];
+            level_indices.extend(level.iter().map(|id| { (*id, level_index) }));
+            levels.push(level.iter().map(|id| { topology.unique_nodes[id].clone() }).collect());
+            level_start = level_end;
+        };
+        Ok(Some(Levels { levels, level_indices }))
+    }
+    /// Tries to build a topological sort from a list of nodes as `sort` does, but whenever several nodes are ready to be placed,
+    /// the one that `compare` orders first goes next. Ties are broken by position in the list, so the order is reproducible.
+    /// The nodes are ranked once and the ready ones are kept in a binary heap, so the sort runs in O(V log V + E).
+    pub fn sort_by<F: FnMut(&N, &N) -> CmpOrdering>(nodes:&[N], mut compare: F) -> Result<Option<Vec<N>>, TopologicalError<N::Id>> {
+        let topology: Topology<N> = Topology::from_nodes(nodes);
+        if !topology.is_consistent() {
+            return Ok(None);
+        };
+        let mut ranking: Vec<usize> = (0..nodes.len()).collect(); // a consistent list has no repeated ids.
+        ranking.sort_by(|left, right| { compare(&nodes[*left], &nodes[*right]) }); // the sort is stable, so ties keep the list order.
+        topology.prioritized_order(nodes, &ranking).map(Some)
+    }
+    /// Sorts as `sort_by`, placing ready nodes in the order they were given.
+    pub fn sort_by_position(nodes:&[N]) -> Result<Option<Vec<N>>, TopologicalError<N::Id>> {
+        Self::sort_by(nodes, |_, _| { CmpOrdering::Equal })
+    }
+    /// Sorts as `sort_by`, placing ready nodes by ascending id.
+    pub fn sort_by_id(nodes:&[N]) -> Result<Option<Vec<N>>, TopologicalError<N::Id>>
+    where
+        N::Id: Ord,
+    {
+        Self::sort_by(nodes, |left, right| { left.id().cmp(&right.id()) })
+    }
+    /// Sorts as `sort_by`, placing ready nodes in the order `compare` gives to their payloads.
+    pub fn sort_by_payload<F: FnMut(&N::Payload, &N::Payload) -> CmpOrdering>(nodes:&[N], mut compare: F) -> Result<Option<Vec<N>>, TopologicalError<N::Id>> {
+        Self::sort_by(nodes, |left, right| { compare(left.payload(), right.payload()) })
+    }
+    /// Sorts as `sort_by`, placing ready nodes by ascending priority of their payloads, e.g. by deadline for an earliest-deadline-first order.
+    /// `Reverse` priorities place the highest first. The priority is evaluated once per node.
+    pub fn sort_by_priority<K: Ord, F: FnMut(&N::Payload) -> K>(nodes:&[N], mut priority: F) -> Result<Option<Vec<N>>, TopologicalError<N::Id>> {
+        let topology: Topology<N> = Topology::from_nodes(nodes);
+        if !topology.is_consistent() {
+            return Ok(None);
+        };
+        let mut ranking: Vec<usize> = (0..nodes.len()).collect();
+        ranking.sort_by_cached_key(|position| { priority(nodes[*position].payload()) });
+        topology.prioritized_order(nodes, &ranking).map(Some)
+    }
+    /// Orders a consistent topology with Kahn's algorithm, always placing next the ready node ranked first.
+    /// `ranking` lists the positions in `nodes` from the first ranked to the last.
+    fn prioritized_order(&self, nodes:&[N], ranking: &[usize]) -> Result<Vec<N>, TopologicalError<N::Id>> {
+        let ranks: HashMap<N::Id, usize> = ranking.iter().enumerate().map(|(rank, position)| { (nodes[*position].id(), rank) }).collect();
+        let mut in_degree_map: HashMap<N::Id, usize> = self.unique_nodes.iter().map(|(id, node)| { (*id, node.in_degree()) }).collect();
+        let mut ready: BinaryHeap<Reverse<usize>> = ranking
+            .iter()
+            .enumerate()
+            .filter(|(_, position)| { nodes[**position].in_degree() == 0 })
+            .map(|(rank, _)| { Reverse(rank) })
+            .collect();
+        let mut ordering: Vec<N::Id> = Vec::with_capacity(ranking.len());
+        while let Some(Reverse(rank)) = ready.pop() {
+            let id = nodes[ranking[rank]].id();
+            ordering.push(id);
+            for outgoing_node_id in self.get_outgoing_edges_by_id(id).into_iter().flatten() {
+                let in_degree = in_degree_map.get_mut(outgoing_node_id).ok_or(TopologicalError::InvalidTopologicalAssumptions)?;
+                assert!(*in_degree > 0); // every edge is relaxed once, so the in-degree can't underflow.
+                *in_degree -= 1;
+                if *in_degree == 0 {
+                    ready.push(Reverse(ranks[outgoing_node_id]));
                 };
-                if ordering.len() == topology_for_sorting.unique_nodes.len() { Ok(Some(ordering)) } else { Ok(None) }
-            },
-            None => Ok(None)
+            };
+        };
+        let (ordering, _) = self.complete_order(nodes, ordering, Vec::new(), &in_degree_map)?;
+        Ok(ordering.into_iter().map(|id| { self.unique_nodes[&id].clone() }).collect())
+    }
+    /// Orders the ids of a consistent topology with Kahn's algorithm, see `sort`, level by level:
+    /// the first level holds the roots in the order they were given, and each level holds the ids released by the previous one.
+    /// Returns the order and the end of each level in it.
+    /// `nodes` are the nodes the topology was built from.
+    fn topological_order(&self, nodes:&[N]) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>> {
+        let mut in_degree_map: HashMap<N::Id, usize> = self.unique_nodes.iter().map(|(id, node)| { (*id, node.in_degree()) }).collect();
+        let mut ordering: Vec<N::Id> = nodes
+            .iter()
+            .map(|node| { node.id() })
+            .filter(|id| { in_degree_map.get(id) == Some(&0) })
+            .collect();
+        let mut level_ends: Vec<usize> = Vec::new();
+        let mut level_start = 0;
+        while level_start < ordering.len() {
+            let level_end = ordering.len();
+            for position in level_start..level_end {
+                if let Some(edges) = self.get_outgoing_edges_by_id(ordering[position]) {
+                    for outgoing_node_id in edges {
+                        let in_degree = in_degree_map.get_mut(outgoing_node_id).ok_or(TopologicalError::InvalidTopologicalAssumptions)?;
+                        assert!(*in_degree > 0); // every edge is relaxed once, so the in-degree can't underflow.
+                        *in_degree -= 1;
+                        if *in_degree == 0 {
+                            ordering.push(*outgoing_node_id);
+                        };
+                    };
+                };
+            };
+            level_ends.push(level_end);
+            level_start = level_end;
+        };
+        self.complete_order(nodes, ordering, level_ends, &in_degree_map)
+    }
+    /// Returns the order if it includes every node, otherwise a `TopologicalError::Cycle` with the nodes left with a positive in-degree.
+    fn complete_order(&self, nodes:&[N], ordering: Vec<N::Id>, level_ends: Vec<usize>, in_degree_map: &HashMap<N::Id, usize>) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>> {
+        if ordering.len() == self.unique_nodes.len() {
+            Ok((ordering, level_ends))
+        } else {
+            let unordered: Vec<N::Id> = nodes
+                .iter()
+                .map(|node| { node.id() })
+                .filter(|id| { in_degree_map.get(id).is_some_and(|in_degree| { *in_degree > 0 }) })
+                .collect();
+            let cycle = self.find_cycle(&unordered[0], in_degree_map);
+            Err(TopologicalError::Cycle { cycle, unordered })
         }
     }
+    /// Walks the ancestors of a node that could not be ordered until an id repeats, and returns the ids of the cycle found in edge order.
+    /// Every node left with a positive in-degree after sorting has at least one ancestor in the same condition, so the walk always closes a cycle.
+    fn find_cycle(&self, start: &N::Id, in_degree_map: &HashMap<N::Id, usize>) -> Vec<N::Id> {
+        let is_unordered = |id: &N::Id| { in_degree_map.get(id).is_some_and(|in_degree| { *in_degree > 0 }) };
+        let mut walk: Vec<N::Id> = Vec::new();
+        let mut positions: HashMap<N::Id, usize> = HashMap::new(); // position of each id in the walk.
+        let mut current = *start;
+        let cycle_start = loop {
+            if let Some(position) = positions.get(&current) {
+                break *position;
+            };
+            positions.insert(current, walk.len());
+            walk.push(current);
+            let node = self.get_unique_node_by_id(current).expect("Invalid topological assumptions."); // the topology is consistent at this stage.
+            current = node
+                .parents()
+                .find(|ancestor| { is_unordered(ancestor) })
+                .expect("Invalid topological assumptions.");
+        };
+        let mut cycle = walk.split_off(cycle_start);
+        cycle.reverse(); // the walk follows ancestors, so it is reversed to follow the edges.
+        cycle
+    }
     /// Calculates the shortest and longest paths from a list of nodes from the *first* node of the list.
     /// Because the algorithm assumes the first node is the starting node from which to calculate distances,
     /// it should not have incoming edges, i.e. left and right reference are None, otherwise a FirstNodeHasIncomingEdges error is returned.
     /// This methods relies on Single Source Shortest and Longest (negated) Path algorithm.
-    pub fn shortest_and_longest_paths(nodes:&[Node<T, U>]) -> Result<Option<HashMap<T, (Option<usize>, Option<usize>)>>, TopologicalError > {
+    /// The topology is built once, and the list is sorted on it before relaxing the edges.
+    pub fn shortest_and_longest_paths(nodes:&[N]) -> Result<Option<PathLengths<N::Id>>, TopologicalError<N::Id> > {
+        Self::shortest_and_longest_paths_with(nodes, Self::from_nodes, Self::topological_order, |topology, topological_order, _, source| {
+            topology.path_lengths(topological_order, source)
+        })
+    }
+    /// Calculates the paths as `shortest_and_longest_paths` does, building the topology with `from_nodes`,
+    /// ordering it with `topological_order` and relaxing its edges with `path_lengths`.
+    fn shortest_and_longest_paths_with<F, O, P>(nodes:&[N], from_nodes: F, topological_order: O, path_lengths: P) -> Result<Option<PathLengths<N::Id>>, TopologicalError<N::Id>>
+    where
+        F: FnOnce(&[N]) -> Self,
+        O: FnOnce(&Self, &[N]) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>>,
+        P: FnOnce(&Self, &[N::Id], &[usize], N::Id) -> PathLengths<N::Id>,
+    {
         if !nodes.is_empty()  {
-            if nodes[0].left.is_some() || nodes[0].right.is_some()
+            if nodes[0].in_degree() > 0
             {
                 return Err(TopologicalError::FirstNodeHasIncomingEdges);
             }
-        } else 
-        {
+        } else {
             return Ok(None); // list is empty.
         };
-        let mut lengths_map: HashMap<T, (Option<usize>, Option<usize>)> = HashMap::new(); // HashMap for accumulating shortest and longest paths for each node in the list.
-        if let Some(topological_order) = Self::sort(nodes)? {  // This algorithm assumes that the list nodes conforms to a topological sort
-            assert!(topological_order.len() == nodes.len(), "Invalid value assumptions.");  // If there exists a topological sort, it includes all unique nodes.
-            let mut topology: Topology<T, U> = Topology::new();
-            for node in nodes.iter() {
-                topology.insert(*node);
-                lengths_map.insert(node.id, (None, None));  // initiates lengths as None for all nodes in the list.
-            };
-            let mut outgoing_edges = // instantiates a variable with the outgoing edges of all nodes.
-                if nodes[0] == topological_order[0] // This assumption relies on the sorting algorithm.
-                && topology.collitions.is_empty()
-                && topology.repeated_nodes.is_empty()
-                && topology.unique_nodes.len() == nodes.len()
-                && topology.is_consistent()
-                {
-                    topology.outgoing_edges
-                } else {
-                    return Err(TopologicalError::InvalidTopologicalAssumptions);
+        let topology: Topology<N> = from_nodes(nodes);
+        if !topology.is_consistent() {
+            return Ok(None);
+        };
+        let (topological_order, level_ends) = topological_order(&topology, nodes)?; // This algorithm assumes that the list nodes conforms to a topological sort
+        if topological_order.len() != nodes.len() { // If there exists a topological sort, it includes all unique nodes.
+            return Err(TopologicalError::InvalidTopologicalAssumptions);
+        };
+        Ok(Some(path_lengths(&topology, &topological_order, &level_ends, nodes[0].id())))
+    }
+    /// Relaxes the edges reaching each id following a topological order of the topology, from the `source` id weighing `zero`,
+    /// with `weight` giving the weight of the edge from a parent id to a child node through the child's branch.
+    /// Nodes unreachable from the source are left with `(None, None)`, and sums incomparable to themselves, e.g. float NaNs, are ignored.
+    fn weighted_path_lengths<W, F>(&self, topological_order: &[N::Id], source: N::Id, zero: W, mut weight: F) -> WeightedPathLengths<N::Id, W>
+    where
+        W: Clone + PartialOrd + Add<Output = W>,
+        F: FnMut(N::Id, &N, Side) -> W,
+    {
+        let mut lengths_map: WeightedPathLengths<N::Id, W> = HashMap::with_capacity(topological_order.len());
+        for id in topological_order {
+            let node = &self.unique_nodes[id];
+            let mut lengths: WeightedPathLength<W> = if *id == source { (Some(zero.clone()), Some(zero.clone())) } else { (None, None) };
+            if *id != source { // the source can't be reached from its ancestors.
+                for (side, ancestor) in node.references() {
+                    let (Some(shortest_distance), Some(longest_distance)) = &lengths_map[&ancestor] else {
+                        continue; // ancestors unreachable from the source don't relax their edges.
+                    };
+                    let edge_weight = weight(ancestor, node, side);
+                    let shortest_weight = shortest_distance.clone() + edge_weight.clone();
+                    let is_comparable = shortest_weight.partial_cmp(&shortest_weight).is_some();
+                    if is_comparable && lengths.0.as_ref().is_none_or(|distance| { shortest_weight < *distance }) {
+                        lengths.0 = Some(shortest_weight);
+                    };
+                    let longest_weight = longest_distance.clone() + edge_weight;
+                    let is_comparable = longest_weight.partial_cmp(&longest_weight).is_some();
+                    if is_comparable && lengths.1.as_ref().is_none_or(|distance| { longest_weight > *distance }) {
+                        lengths.1 = Some(longest_weight);
+                    };
                 };
-            if topological_order[0] == nodes[0] {
-                assert_eq!(lengths_map.insert(nodes[0].id, (Some(0),Some(0))), Some((None, None))); // all nodes have been initiated in lengths_map previously with value (None, None)
             };
-
-            let mut reverse_topological_order: Vec<&Node<T, U>> = topological_order.iter().rev().collect(); // The topological order is reversed to iterate over the last element in the memory layout of the vector.
-            let nodes_length = loop {
-                if let Some(last_node_from_reverse_topological_order) = reverse_topological_order.pop() {
-                    let node_distance = &lengths_map.get(&last_node_from_reverse_topological_order.id).expect("Invalid topological assumptions.").clone(); // all nodes have been inserted to lengths_map previously at this stage.
-                    match outgoing_edges.remove(&last_node_from_reverse_topological_order.id) {
-                        Some(edges) => {
-                            for node_id in edges {
-                                if let Some(outgoing_node_path_lengths) = lengths_map.get_mut(&node_id) {
-                                    if let Some(shortest_distance) = node_distance.0 {
-                                        let weight = shortest_distance + 1;
-                                        match outgoing_node_path_lengths.0 {
-                                            Some(outgoing_node_path_shortest_distance) => {
-                                                if outgoing_node_path_shortest_distance > weight {
-                                                    outgoing_node_path_lengths.0 = Some(weight);
-                                                };
-                                            },
-                                            None => {
-                                                outgoing_node_path_lengths.0 = Some(weight);
-                                            }
-                                        };
-                                    };
-                                    if let Some(longest_distance) = node_distance.1 {
-                                        let weight = longest_distance + 1;
-                                        match outgoing_node_path_lengths.1 {
-                                            Some(outgoing_node_path_longest_distance) => {
-                                                if outgoing_node_path_longest_distance < weight {
-                                                    outgoing_node_path_lengths.1 = Some(weight);
-                                                };
-                                            },
-                                            None => {
-                                                outgoing_node_path_lengths.1 = Some(weight);
-                                            }
-                                        }
-                                    };
-                                } else {
-                                    return Err(TopologicalError::InvalidTopologicalAssumptions);
-                                }
-                            }
-                        },
-                        None => {}
-                    }
-                } else {
-                    break lengths_map;
-                }
-
+            lengths_map.insert(*id, lengths);
+        };
+        lengths_map
+    }
+    /// Relaxes the outgoing edges of each id following a topological order of the topology from the `source` id.
+    /// Nodes unreachable from the source are left with `(None, None)`.
+    fn path_lengths(&self, topological_order: &[N::Id], source: N::Id) -> PathLengths<N::Id> {
+        let mut lengths_map: PathLengths<N::Id> = self.unique_nodes.keys().map(|id| { (*id, (None, None)) }).collect(); // initiates lengths as None for all nodes.
+        lengths_map.insert(source, (Some(0), Some(0)));
+        for id in topological_order {
+            let (Some(shortest_distance), Some(longest_distance)) = lengths_map[id] else {
+                continue; // nodes unreachable from the source don't relax their edges.
             };
-            Ok(Some(nodes_length))
-        } else {
-            Ok(None)
-        }
+            for node_id in self.get_outgoing_edges_by_id(*id).into_iter().flatten() {
+                let outgoing_node_path_lengths = lengths_map.get_mut(node_id).expect("Invalid topological assumptions."); // the topology is consistent.
+                let shortest_weight = shortest_distance + 1;
+                if outgoing_node_path_lengths.0.is_none_or(|distance| { distance > shortest_weight }) {
+                    outgoing_node_path_lengths.0 = Some(shortest_weight);
+                };
+                let longest_weight = longest_distance + 1;
+                if outgoing_node_path_lengths.1.is_none_or(|distance| { distance < longest_weight }) {
+                    outgoing_node_path_lengths.1 = Some(longest_weight);
+                };
+            };
+        };
+        lengths_map
     }
     /// Breath-First Search returns threads upto all nodes starting from the origin 
     /// marked as the first node id from which the iteration of this algorithm started from, i.e. first call arguments.
-    fn bfs_visit(topology: &Self, id: T, backtrace: &mut Vec<T>, paths_collection: &mut Vec<Vec<T>>) {
+    fn bfs_visit(topology: &Self, id: N::Id, backtrace: &mut Vec<N::Id>, paths_collection: &mut Vec<Vec<N::Id>>) {
         match topology.get_outgoing_edges_by_id(id) {
             Some(edges) => {
                 if backtrace.is_empty() {
@@ -304,11 +512,388 @@ impl<T: Eq + Hash + PartialEq + Copy + std::fmt::Debug, U: Eq + Hash + PartialEq
         };
     }
     /// Evaluates all possible paths in the dag from a base node given by id in the argument, to all the other nodes in the dag.
-    pub fn bfs_all_paths(topology: &Self, id: T) -> Option<Vec<Vec<T>>> {
-        let mut collection: Vec<Vec<T>> = Vec::new();
+    pub fn bfs_all_paths(topology: &Self, id: N::Id) -> Option<Vec<Vec<N::Id>>> {
+        Some(topology.all_paths(id))
+    }
+    fn all_paths(&self, id: N::Id) -> Vec<Vec<N::Id>> {
+        let mut collection: Vec<Vec<N::Id>> = Vec::new();
         let empty_vector = &mut Vec::new();
-        Topology::bfs_visit(topology, id, empty_vector, &mut collection);
-        Some(collection)
+        Topology::bfs_visit(self, id, empty_vector, &mut collection);
+        collection
+    }
+}
+
+/// Parallel versions of the analyses, evaluated with rayon. They give the same results as the sequential ones,
+/// and need nodes and ids that can be shared between threads.
+#[cfg(feature = "parallel")]
+impl<N> Topology<N>
+where
+    N: DagNode + Clone + Debug + Send + Sync,
+    N::Id: Send + Sync,
+{
+    /// Constructs a topology from a slice of nodes as `try_from_slice` does, in parallel.
+    pub fn par_try_from_slice(node_list:&[N]) -> Result<Self, ValidationReport<N::Id>> {
+        Topology::parallel_from_nodes(node_list).validated()
+    }
+    /// Tries to build a topological sort from a list of nodes as `sort` does, in parallel.
+    pub fn par_sort(nodes:&[N]) -> Result<Option<Vec<N>>, TopologicalError<N::Id>> {
+        Self::sort_with(nodes, Self::parallel_from_nodes, Self::parallel_topological_order)
+    }
+    /// Tries to build a topological sort from a list of nodes as `sort_levels` does, in parallel.
+    pub fn par_sort_levels(nodes:&[N]) -> Result<Option<Levels<N>>, TopologicalError<N::Id>> {
+        Self::sort_levels_with(nodes, Self::parallel_from_nodes, Self::parallel_topological_order)
+    }
+    /// Calculates the shortest and longest paths from the *first* node of the list as `shortest_and_longest_paths` does, in parallel.
+    pub fn par_shortest_and_longest_paths(nodes:&[N]) -> Result<Option<PathLengths<N::Id>>, TopologicalError<N::Id>> {
+        Self::shortest_and_longest_paths_with(nodes, Self::parallel_from_nodes, Self::parallel_topological_order, Self::parallel_path_lengths)
+    }
+    /// Evaluates all possible paths from a base node as `bfs_all_paths` does, in parallel.
+    pub fn par_bfs_all_paths(topology: &Self, id: N::Id) -> Option<Vec<Vec<N::Id>>> {
+        Some(topology.parallel_all_paths(id))
+    }
+    /// Parallel version of `from_nodes`. Collitions are detected from the first position and the number of occurrences
+    /// of each id and of each node compared by all fields: the first node of an id is unique, the first occurrence of any other
+    /// node with that id is a collition, and every further occurrence of a node is repeated.
+    fn parallel_from_nodes(node_list:&[N]) -> Self {
+        let first_positions: HashMap<N::Id, usize> = node_list
+            .par_iter()
+            .enumerate()
+            .fold(HashMap::new, |mut first_positions: HashMap<N::Id, usize>, (position, node)| {
+                first_positions.entry(node.id()).or_insert(position);
+                first_positions
+            })
+            .reduce(HashMap::new, |mut left, right| {
+                for (id, position) in right {
+                    let first_position = left.entry(id).or_insert(position);
+                    *first_position = (*first_position).min(position);
+                };
+                left
+            });
+        let occurrences: HashMap<CollidingNode<N>, (usize, usize)> = node_list
+            .par_iter()
+            .enumerate()
+            .fold(HashMap::new, |mut occurrences: HashMap<CollidingNode<N>, (usize, usize)>, (position, node)| {
+                occurrences.entry(node.clone().into()).or_insert((position, 0)).1 += 1;
+                occurrences
+            })
+            .reduce(HashMap::new, |mut left, right| {
+                for (node, (position, count)) in right {
+                    let occurrence = left.entry(node).or_insert((position, 0));
+                    occurrence.0 = occurrence.0.min(position);
+                    occurrence.1 += count;
+                };
+                left
+            });
+        let is_unique = |node: &CollidingNode<N>, first_position: &usize| { first_positions[&node.0.id()] == *first_position };
+        let unique_nodes: HashMap<N::Id, N> = occurrences
+            .par_iter()
+            .filter(|(node, (first_position, _))| { is_unique(node, first_position) })
+            .map(|(node, _)| { (node.0.id(), node.0.clone()) })
+            .collect();
+        let collitions: HashSet<CollidingNode<N>> = occurrences
+            .par_iter()
+            .filter(|(node, (first_position, _))| { !is_unique(node, first_position) })
+            .map(|(node, _)| { node.clone() })
+            .collect();
+        let repeated_nodes: HashMap<N::Id, HashSet<CollidingNode<N>>> = occurrences
+            .par_iter()
+            .filter(|(_, (_, count))| { *count > 1 })
+            .fold(HashMap::new, |mut repeated_nodes: HashMap<N::Id, HashSet<CollidingNode<N>>>, (node, _)| {
+                repeated_nodes.entry(node.0.id()).or_default().insert(node.clone());
+                repeated_nodes
+            })
+            .reduce(HashMap::new, |mut left, right| {
+                for (id, repeated_nodes_set) in right {
+                    left.entry(id).or_default().extend(repeated_nodes_set);
+                };
+                left
+            });
+        // edges are listed in the order their nodes were inserted, as `collect_edges` does.
+        let mut unique_positions: Vec<usize> = first_positions.into_par_iter().map(|(_, position)| { position }).collect();
+        unique_positions.par_sort_unstable();
+        let outgoing_edges: HashMap<N::Id, Vec<N::Id>> = unique_positions
+            .par_iter()
+            .fold(HashMap::new, |mut outgoing_edges: HashMap<N::Id, Vec<N::Id>>, position| {
+                let node = &node_list[*position];
+                for ancestor in node.parents() {
+                    outgoing_edges.entry(ancestor).or_default().push(node.id());
+                };
+                outgoing_edges
+            })
+            .reduce(HashMap::new, |mut left, right| {
+                for (ancestor, edges) in right {
+                    left.entry(ancestor).or_default().extend(edges);
+                };
+                left
+            });
+        Topology {
+            all_nodes: occurrences.into_par_iter().map(|(node, _)| { node }).collect(),
+            unique_nodes,
+            unique_ids: unique_positions.into_iter().map(|position| { node_list[position].id() }).collect(),
+            collitions,
+            repeated_nodes,
+            outgoing_edges,
+        }
+    }
+    /// Parallel version of `topological_order`. The edges of each level are relaxed in parallel,
+    /// and the released ids are placed as the sequential version does, i.e. by the position of the last edge releasing them.
+    fn parallel_topological_order(&self, nodes:&[N]) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>> {
+        let in_degrees: HashMap<N::Id, AtomicUsize> = self.unique_nodes
+            .par_iter()
+            .map(|(id, node)| { (*id, AtomicUsize::new(node.in_degree())) })
+            .collect();
+        let mut ordering: Vec<N::Id> = nodes
+            .iter()
+            .map(|node| { node.id() })
+            .filter(|id| { in_degrees.get(id).is_some_and(|in_degree| { in_degree.load(Ordering::Relaxed) == 0 }) })
+            .collect();
+        let mut level_ends: Vec<usize> = Vec::new();
+        let mut level_start = 0;
+        while level_start < ordering.len() {
+            let level_end = ordering.len();
+            let edges: Vec<N::Id> = ordering[level_start..level_end]
+                .par_iter()
+                .flat_map_iter(|id| { self.get_outgoing_edges_by_id(*id).into_iter().flatten().copied() })
+                .collect();
+            // number of edges to each id in the level, and the position of the last one.
+            let level_edges: HashMap<N::Id, (usize, usize)> = edges
+                .par_iter()
+                .copied()
+                .enumerate()
+                .fold(HashMap::new, |mut level_edges: HashMap<N::Id, (usize, usize)>, (position, id)| {
+                    let edges = level_edges.entry(id).or_insert((0, position));
+                    edges.0 += 1;
+                    edges.1 = position;
+                    level_edges
+                })
+                .reduce(HashMap::new, |mut left, right| {
+                    for (id, (count, last_position)) in right {
+                        let edges = left.entry(id).or_insert((0, last_position));
+                        edges.0 += count;
+                        edges.1 = edges.1.max(last_position);
+                    };
+                    left
+                });
+            let mut released: Vec<(usize, N::Id)> = level_edges
+                .into_par_iter()
+                .map(|(id, (count, last_position))| {
+                    let in_degree = in_degrees.get(&id).ok_or(TopologicalError::InvalidTopologicalAssumptions)?.fetch_sub(count, Ordering::Relaxed);
+                    assert!(in_degree >= count); // every edge is relaxed once, so the in-degree can't underflow.
+                    Ok((in_degree == count).then_some((last_position, id)))
+                })
+                .filter_map(Result::transpose)
+                .collect::<Result<Vec<(usize, N::Id)>, TopologicalError<N::Id>>>()?;
+            released.par_sort_unstable_by_key(|(last_position, _)| { *last_position });
+            ordering.extend(released.into_iter().map(|(_, id)| { id }));
+            level_ends.push(level_end);
+            level_start = level_end;
+        };
+        let in_degree_map: HashMap<N::Id, usize> = in_degrees.into_iter().map(|(id, in_degree)| { (id, in_degree.into_inner()) }).collect();
+        self.complete_order(nodes, ordering, level_ends, &in_degree_map)
+    }
+    /// Parallel version of `path_lengths`. The ids of a level don't reference each other,
+    /// so their edges are relaxed in parallel once the previous levels are done.
+    fn parallel_path_lengths(&self, topological_order: &[N::Id], level_ends: &[usize], source: N::Id) -> PathLengths<N::Id> {
+        // distances are kept plus one, so that 0 marks the nodes unreachable from the source.
+        let distances: HashMap<N::Id, (AtomicUsize, AtomicUsize)> = self.unique_nodes
+            .par_iter()
+            .map(|(id, _)| { (*id, (AtomicUsize::new(0), AtomicUsize::new(0))) })
+            .collect();
+        if let Some((shortest_distance, longest_distance)) = distances.get(&source) {
+            shortest_distance.store(1, Ordering::Relaxed);
+            longest_distance.store(1, Ordering::Relaxed);
+        };
+        let mut level_start = 0;
+        for level_end in level_ends {
+            topological_order[level_start..*level_end].par_iter().for_each(|id| {
+                let shortest_distance = distances[id].0.load(Ordering::Relaxed);
+                let longest_distance = distances[id].1.load(Ordering::Relaxed);
+                if shortest_distance == 0 {
+                    return; // nodes unreachable from the source don't relax their edges.
+                };
+                for node_id in self.get_outgoing_edges_by_id(*id).into_iter().flatten() {
+                    let (outgoing_node_shortest_distance, outgoing_node_longest_distance) = distances.get(node_id).expect("Invalid topological assumptions."); // the topology is consistent.
+                    let _ = outgoing_node_shortest_distance.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |distance| {
+                        (distance == 0 || distance > shortest_distance + 1).then_some(shortest_distance + 1)
+                    });
+                    outgoing_node_longest_distance.fetch_max(longest_distance + 1, Ordering::Relaxed);
+                };
+            });
+            level_start = *level_end;
+        };
+        distances
+            .into_par_iter()
+            .map(|(id, (shortest_distance, longest_distance))| {
+                (id, (shortest_distance.into_inner().checked_sub(1), longest_distance.into_inner().checked_sub(1)))
+            })
+            .collect()
+    }
+    /// Parallel version of `all_paths`. The paths through each edge of the base node are evaluated in parallel,
+    /// and collected in the order of the edges.
+    fn parallel_all_paths(&self, id: N::Id) -> Vec<Vec<N::Id>> {
+        let Some(edges) = self.get_outgoing_edges_by_id(id) else {
+            return Vec::new();
+        };
+        edges
+            .par_iter()
+            .flat_map_iter(|node_id| {
+                let mut thread = vec![id, *node_id];
+                let mut collection = vec![thread.clone()];
+                Self::bfs_visit(self, *node_id, &mut thread, &mut collection);
+                collection
+            })
+            .collect()
+    }
+}
+
+/// `Analysis<N>` is an analysis context built once from a list of nodes.
+/// It caches the validated topology and one of its topological orders, so sorting, path lengths and traversals don't rebuild them.
+#[derive(Debug, Clone)]
+pub struct Analysis<N: DagNode> {
+    topology: Topology<N>,
+    topological_order: Vec<N::Id>,
+    level_ends: Vec<usize>, // end of each level of the order, see `Topology::topological_order`.
+}
+
+impl<N: DagNode + Clone + Debug> Analysis<N> {
+    /// Builds the analysis context of a list of nodes.
+    /// If the list is not consistent it returns None,
+    /// and if the list has a cycle it returns a `TopologicalError::Cycle` as `Topology::sort` does.
+    pub fn new(nodes:&[N]) -> Result<Option<Self>, TopologicalError<N::Id>> {
+        Self::new_with(nodes, Topology::from_nodes, Topology::topological_order)
+    }
+    /// Builds the analysis context as `new` does, building the topology with `from_nodes` and ordering it with `topological_order`.
+    fn new_with<F, O>(nodes:&[N], from_nodes: F, topological_order: O) -> Result<Option<Self>, TopologicalError<N::Id>>
+    where
+        F: FnOnce(&[N]) -> Topology<N>,
+        O: FnOnce(&Topology<N>, &[N]) -> Result<LeveledOrder<N::Id>, TopologicalError<N::Id>>,
+    {
+        let topology: Topology<N> = from_nodes(nodes);
+        if !topology.is_consistent() {
+            return Ok(None);
+        };
+        let (topological_order, level_ends) = topological_order(&topology, nodes)?;
+        Ok(Some(Analysis { topology, topological_order, level_ends }))
+    }
+    /// The validated topology.
+    pub fn topology(&self) -> &Topology<N> {
+        &self.topology
+    }
+    /// The cached topological order, by id.
+    pub fn order(&self) -> &[N::Id] {
+        &self.topological_order
+    }
+    /// Gets a reference to the node with the given id.
+    pub fn get(&self, id: &N::Id) -> Option<&N> {
+        self.topology.unique_nodes.get(id)
+    }
+    /// The ids of each level of the cached order, see `Levels`.
+    pub fn levels(&self) -> Vec<&[N::Id]> {
+        let mut level_start = 0;
+        self
+            .level_ends
+            .iter()
+            .map(|level_end| {
+                let level = &self.topological_order[level_start..*level_end];
+                level_start = *level_end;
+                level
+            })
+            .collect()
+    }
+    /// Level of each node of the cached order, by id, see `Levels`.
+    pub fn level_indices(&self) -> HashMap<N::Id, usize> {
+        self
+            .levels()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(level_index, level)| { level.iter().map(move |id| { (*id, level_index) }) })
+            .collect()
+    }
+    /// Number of nodes analyzed.
+    pub fn len(&self) -> usize {
+        self.topological_order.len()
+    }
+    /// Returns `true` if there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.topological_order.is_empty()
+    }
+    /// The nodes in the cached topological order, see `Topology::sort`.
+    pub fn sort(&self) -> Vec<&N> {
+        self
+            .topological_order
+            .iter()
+            .map(|id| { &self.topology.unique_nodes[id] })
+            .collect()
+    }
+    /// Calculates the shortest and longest paths from the `source` node to every node, relaxing the edges on the cached order.
+    /// Unlike `Topology::shortest_and_longest_paths`, the source may have incoming edges; nodes unreachable from it get `(None, None)`.
+    /// Returns None if the source is not present.
+    pub fn shortest_and_longest_paths(&self, source: &N::Id) -> Option<PathLengths<N::Id>> {
+        self.topology.unique_nodes.contains_key(source).then(|| { self.topology.path_lengths(&self.topological_order, *source) })
+    }
+    /// Calculates the shortest and longest paths from the `source` node to every node as `shortest_and_longest_paths` does,
+    /// but each edge weighs what `weight` gives to its parent id, child id and the branch of the child referencing the parent.
+    /// Weights are any type that can be cloned, added and compared, e.g. integers, floats or `Duration`s, and the source weighs `zero`.
+    /// Sums incomparable to themselves, e.g. float NaNs, are ignored, so a node only reached through them is left as unreachable.
+    /// The edges are relaxed on the cached order in O(V + E). Returns None if the source is not present.
+    pub fn weighted_shortest_and_longest_paths<W, F>(&self, source: &N::Id, zero: W, mut weight: F) -> Option<WeightedPathLengths<N::Id, W>>
+    where
+        W: Clone + PartialOrd + Add<Output = W>,
+        F: FnMut(N::Id, N::Id, Side) -> W,
+    {
+        self.topology.unique_nodes.contains_key(source).then(|| {
+            self.topology.weighted_path_lengths(&self.topological_order, *source, zero, |parent, child, side| { weight(parent, child.id(), side) })
+        })
+    }
+    /// Calculates the shortest and longest paths from the `source` node as `weighted_shortest_and_longest_paths` does,
+    /// but each edge weighs what `weight` gives to the payload of its child, e.g. the duration of a task for its critical path.
+    /// Returns None if the source is not present.
+    pub fn payload_weighted_shortest_and_longest_paths<W, F>(&self, source: &N::Id, zero: W, mut weight: F) -> Option<WeightedPathLengths<N::Id, W>>
+    where
+        W: Clone + PartialOrd + Add<Output = W>,
+        F: FnMut(&N::Payload) -> W,
+    {
+        self.topology.unique_nodes.contains_key(source).then(|| {
+            self.topology.weighted_path_lengths(&self.topological_order, *source, zero, |_, child, _| { weight(child.payload()) })
+        })
+    }
+    /// Evaluates all possible paths from the `source` node, see `Topology::bfs_all_paths`.
+    pub fn bfs_all_paths(&self, source: &N::Id) -> Vec<Vec<N::Id>> {
+        self.topology.all_paths(*source)
+    }
+}
+
+/// Parallel versions of the analyses, see the parallel versions of `Topology`.
+#[cfg(feature = "parallel")]
+impl<N> Analysis<N>
+where
+    N: DagNode + Clone + Debug + Send + Sync,
+    N::Id: Send + Sync,
+{
+    /// Builds the analysis context of a list of nodes as `new` does, in parallel.
+    pub fn par_new(nodes:&[N]) -> Result<Option<Self>, TopologicalError<N::Id>> {
+        Self::new_with(nodes, Topology::parallel_from_nodes, Topology::parallel_topological_order)
+    }
+    /// Calculates the shortest and longest paths from the `source` node as `shortest_and_longest_paths` does,
+    /// relaxing the edges of each level of the cached order in parallel.
+    pub fn par_shortest_and_longest_paths(&self, source: &N::Id) -> Option<PathLengths<N::Id>> {
+        self.topology.unique_nodes.contains_key(source).then(|| { self.topology.parallel_path_lengths(&self.topological_order, &self.level_ends, *source) })
+    }
+    /// Evaluates all possible paths from the `source` node as `bfs_all_paths` does, in parallel.
+    pub fn par_bfs_all_paths(&self, source: &N::Id) -> Vec<Vec<N::Id>> {
+        self.topology.parallel_all_paths(*source)
+    }
+}
+
+/// Builds the topology of the nodes present in a dag. Collitions collected by the dag are left out,
+/// and nodes with unresolved references make the topology inconsistent, see `validate`.
+impl<N: DagNode + Clone + Debug> From<&Dag<N>> for Topology<N> {
+    fn from(dag: &Dag<N>) -> Self {
+        let mut topology = Topology::new();
+        for node in dag.nodes() {
+            topology.insert(node.clone());
+        };
+        topology
     }
 }
 
@@ -345,13 +930,13 @@ fn insert_nodes_in_topology_analysis() {
     };
     assert!(topology.is_consistent()); // Upto this stage, the topology is consistent.
     let colliding_node = Node::new(4, None, None, ());
-    assert_eq!(topology.insert(colliding_node), Some(node_e.into())); // Tries to insert a node with the same id of an already indexed node in the topology analysis.
+    assert_eq!(topology.insert(colliding_node), Some(colliding_node.into())); // Tries to insert a node with the same id of an already indexed node in the topology analysis.
     assert_eq!(topology.collitions.len(), 1); // There's one collition.
     assert_eq!(topology.get_unique_node_by_id(4), Some(node_e)); // Checks that the Node in the collection of unique nodes is still the first one added by the same id.
     assert_eq!(topology.collitions.get(&node_e.into()), None); // The first added node_e will not be present in the collitions set.
     assert_eq!(topology.collitions.get(&colliding_node.into()), Some(&colliding_node.into()));  // The colliding node will be present in the collitions set.
     assert_eq!(topology.repeated_nodes.len(), 0);  // There is no repeated nodes yet.
-    assert_eq!(topology.insert(colliding_node), Some(node_e.into()));
+    assert_eq!(topology.insert(colliding_node), Some(colliding_node.into()));
     assert_eq!(topology.repeated_nodes.len(), 1); // There is one repeated node i.e. the colliding node.
     assert_eq!(topology.insert(node_b), Some(node_b.into())); // Tries to insert node_b again.
     assert_eq!(topology.collitions.len(), 1); // The number of collitions is still 1.
@@ -371,7 +956,7 @@ fn insert_nodes_in_topology_analysis() {
 }
 
 fn insert_node_with_inexistent_references_is_inconsistent() {
-    let mut topology: Topology<u32, ()> = Topology::new();
+    let mut topology: Topology<Node<u32, ()>> = Topology::new();
     let node = Node::new(1, None, Some(2), ());
     topology.insert(node);
     assert_eq!(topology.collitions.len(), 0);
@@ -380,6 +965,49 @@ fn insert_node_with_inexistent_references_is_inconsistent() {
 }
 
 #[test]
+fn validation_report_lists_issues_by_id() {
+    let node_a = Node::new(0, None, None, ());
+    let node_b = Node::new(1, Some(0), Some(0), ());
+    let node_c = Node::new(2, Some(2), Some(7), ());
+    let node_d = Node::new(3, Some(1), None, ());
+    let colliding_node = Node::new(3, Some(2), None, ());
+    let node_list = [node_a, node_b, node_c, node_d, node_d, colliding_node];
+    let Err(report) = Topology::try_from_slice(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert!(!report.is_consistent());
+    assert_eq!(report.dangling_references, vec![DanglingReference { id: 2, side: Side::Right, missing: 7 }]);
+    assert_eq!(report.repeated_ids, vec![3]);
+    assert_eq!(report.collitions, vec![3]);
+    assert_eq!(report.self_references, vec![2]);
+    assert_eq!(report.duplicate_parents, vec![1]);
+    assert!(Topology::from_slice(&node_list).is_none());
+}
+
+#[test]
+fn validation_report_follows_the_list_order() {
+    let node_list: Vec<Node<u32, u8>> = [9, 4, 7, 1, 8, 2]
+        .into_iter()
+        .flat_map(|id| { [Node::new(id, None, Some(id + 100), 0), Node::new(id, None, None, 1), Node::new(id, None, None, 1)] })
+        .collect(); // each id has a dangling reference, and a collition listed twice.
+    let Err(report) = Topology::try_from_slice(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(report.collitions, vec![9, 4, 7, 1, 8, 2]);
+    assert_eq!(report.repeated_ids, vec![9, 4, 7, 1, 8, 2]);
+    assert_eq!(report.dangling_references.iter().map(|reference| { reference.id }).collect::<Vec<u32>>(), vec![9, 4, 7, 1, 8, 2]);
+}
+
+#[test]
+fn validation_report_of_consistent_topology() {
+    let node_a = Node::new(0, None, None, ());
+    let node_b = Node::new(1, Some(0), Some(0), ());
+    let node_c = Node::new(2, Some(0), Some(1), ());
+    let Ok(topology) = Topology::try_from_slice(&[node_a, node_b, node_c]) else { panic!("Invalid topological assumptions for this test data.") };
+    let report = topology.validate();
+    assert!(report.is_consistent());
+    assert!(!report.is_empty()); // duplicate parents are reported but don't make the topology inconsistent.
+    assert_eq!(report.duplicate_parents, vec![1]);
+}
+
+#[test]
+#[allow(clippy::len_zero)] // baseline test, kept as written.
 fn topological_order() {
     let node_a = Node::new(0,None,None,());
     let node_b = Node::new(1,Some(0),None,());
@@ -398,6 +1026,7 @@ fn topological_order() {
 }
 
 #[test]
+#[allow(clippy::len_zero)] // baseline test, kept as written.
 fn another_topological_order() {
     let node_a = Node::new(35,None,None,());
     let node_b = Node::new(42,Some(35),None,());
@@ -432,8 +1061,50 @@ fn non_dag_topological_order() {
     let node_h = Node::new(1,Some(50), Some(52), ());
     let node_i = Node::new(0,Some(50), Some(1), ());
     let node_list = [node_a, node_b, node_c, node_d, node_e, node_f, node_g, node_h, node_i];
-    let ordering = Topology::sort(&node_list).expect("Invalid value assumptions.");
-    assert_eq!(ordering, None);
+    let Err(TopologicalError::Cycle { cycle, unordered }) = Topology::sort(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(cycle, vec![52, 1, 0, 51]); // 52 -> 1 -> 0 -> 51 -> 52
+    assert_eq!(unordered, vec![51, 101, 52, 1, 0]); // 101 descends from the cycle, so it can't be ordered either.
+}
+
+#[test]
+fn self_referencing_node_is_a_cycle() {
+    let node_a = Node::new(0, None, None, ());
+    let node_b = Node::new(1, Some(0), Some(1), ());
+    let Err(TopologicalError::Cycle { cycle, unordered }) = Topology::sort(&[node_a, node_b]) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(cycle, vec![1]);
+    assert_eq!(unordered, vec![1]);
+}
+
+#[test]
+fn cycle_is_reported_in_edge_order() {
+    let node_a = Node::new(0, None, None, ());
+    let node_b = Node::new(1, Some(0), Some(3), ());
+    let node_c = Node::new(2, Some(1), None, ());
+    let node_d = Node::new(3, Some(2), None, ());
+    let node_list = [node_a, node_b, node_c, node_d];
+    let Err(TopologicalError::Cycle { cycle, unordered }) = Topology::sort(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(unordered, vec![1, 2, 3]);
+    assert_eq!(cycle.len(), 3);
+    for (position, id) in cycle.iter().enumerate() {
+        let child = node_list.iter().find(|node| { node.id == cycle[(position + 1) % cycle.len()] }).expect("Invalid value assumptions.");
+        assert!(child.left == Some(*id) || child.right == Some(*id)); // each id in the cycle is a parent of the next one.
+    };
+}
+
+#[test]
+fn topological_order_of_long_reversed_chain() {
+    let chain_length: u32 = 200_000;
+    let node_list: Vec<Node<u32, ()>> = (0..chain_length)
+        .rev()
+        .map(|id| {
+            if id == 0 { Node::new(id, None, None, ()) } else { Node::new(id, Some(id - 1), None, ()) }
+        })
+        .collect(); // every node is listed before its ancestor.
+    let ordering = Topology::sort(&node_list).expect("Invalid value assumptions.").expect("Invalid value assumptions.");
+    assert_eq!(ordering.len(), node_list.len());
+    for (position, node) in ordering.iter().enumerate() {
+        assert_eq!(node.id, position as u32); // a chain has a single topological order.
+    };
 }
 
 #[test]
@@ -446,7 +1117,7 @@ fn shortest_and_longest_paths() {
     let node_e = Node::new(6, Some(3), Some(3), ());
     let Ok(Some(sorted)) = Topology::sort(&[node_prime, node_a, node_b, node_c, node_d, node_e]) else { panic!("Invalid topological assumptions for this test data.") };
     let Ok(Some(shortest_and_longest)) = Topology::shortest_and_longest_paths(&sorted) else { panic!("Invalid topological assumptions for this test data.") };
-    let _printable: Vec<(&u32, &(Option<usize>, Option<usize>))> = shortest_and_longest.iter().collect();
+    let _printable: Vec<(&u32, &PathLength)> = shortest_and_longest.iter().collect();
     // println!("shortest and longest : {:?}", printable);
 }
 
@@ -466,7 +1137,7 @@ fn another_shortest_and_longest_paths() {
     let node_list = [node_a, node_b, node_c, node_d, node_e, node_f, node_g, node_h, node_i, node_j, node_k];
     let Ok(Some(sorted)) = Topology::sort(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
     let Ok(Some(shortest_and_longest)) = Topology::shortest_and_longest_paths(&sorted) else { panic!("Invalid topological assumptions for this test data.") };
-    let _printable: Vec<(&u32, &(Option<usize>, Option<usize>))> = shortest_and_longest.iter().collect();
+    let _printable: Vec<(&u32, &PathLength)> = shortest_and_longest.iter().collect();
     // println!("another shortest and longest : {:?}", printable);
 }
 
@@ -506,4 +1177,344 @@ fn bfs_threads_no_double_edges() {
     assert_eq!(bfs_all_paths.len(), 10);
     assert_eq!(all_paths_size_sum, 33);
     assert_eq!((average_node_size*10_000.0).round(), 33_000.0); // compares truncated significant
-}
\ No newline at end of file
+}
+
+#[test]
+fn nary_topological_order_and_paths() {
+    let node_a = NaryNode::new(0, vec![], ());
+    let node_b = NaryNode::new(1, vec![0], ());
+    let node_c = NaryNode::new(2, vec![0], ());
+    let node_d = NaryNode::new(3, vec![0, 1, 2], ());
+    let node_e = NaryNode::new(4, vec![3, 2, 1, 0], ());
+    let node_list = [node_a, node_b, node_c, node_d, node_e];
+    let Ok(Some(sorted)) = Topology::sort(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(sorted.len(), node_list.len());
+    for (position, node) in sorted.iter().enumerate() {
+        assert!(node.parents.iter().all(|parent| { sorted[..position].iter().any(|ordered| { ordered.id == *parent }) })); // every ancestor is ordered first.
+    };
+    let Ok(Some(shortest_and_longest)) = Topology::shortest_and_longest_paths(&sorted) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(shortest_and_longest.get(&4), Some(&(Some(1), Some(3))));
+    assert_eq!(shortest_and_longest.get(&3), Some(&(Some(1), Some(2))));
+    let Some(topology) = Topology::from_slice(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    let Some(bfs_all_paths) = Topology::bfs_all_paths(&topology, 0) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(bfs_all_paths.len(), 11);
+}
+
+#[test]
+fn nary_and_binary_nodes_share_the_same_topology() {
+    let node_prime = Node::new(1, None, None, ());
+    let node_a = Node::new(2, Some(1), None, ());
+    let node_b = Node::new(3, Some(1), Some(2), ());
+    let node_c = Node::new(4, Some(2), None, ());
+    let node_d = Node::new(5, Some(3), Some(6), ());
+    let node_e = Node::new(6, Some(3), None, ());
+    let node_list = [node_prime, node_a, node_b, node_c, node_d, node_e];
+    let nary_node_list: Vec<NaryNode<u32, ()>> = node_list.iter().map(|node| { NaryNode::from(*node) }).collect();
+    let Some(topology) = Topology::from_slice(&node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    let Some(nary_topology) = Topology::from_slice(&nary_node_list) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(topology.edge_sum(), nary_topology.edge_sum());
+    assert_eq!(
+        Topology::bfs_all_paths(&topology, 1).map(|paths| { paths.len() }),
+        Topology::bfs_all_paths(&nary_topology, 1).map(|paths| { paths.len() }),
+    );
+}
+
+#[test]
+fn nary_validation_report() {
+    let node_a = NaryNode::new(0, vec![], ());
+    let node_b = NaryNode::new(1, vec![0, 9, 0, 1], ());
+    let Err(report) = Topology::try_from_slice(&[node_a, node_b]) else { panic!("Invalid topological assumptions for this test data.") };
+    assert_eq!(report.dangling_references, vec![DanglingReference { id: 1, side: Side::Parent(1), missing: 9 }]);
+    assert_eq!(report.duplicate_parents, vec![1]);
+    assert_eq!(report.self_references, vec![1]);
+}
+
+#[test]
+fn analysis_caches_topology_and_order() {
+    let node_list = [
+        Node::new(5, Some(3), Some(4), ()),
+        Node::new(0, None, None, ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(2, None, Some(0), ()),
+        Node::new(3, Some(0), Some(1), ()),
+        Node::new(4, Some(2), Some(1), ()),
+        Node::new(6, None, None, ()),
+    ];
+    let Ok(Some(analysis)) = Analysis::new(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(analysis.len(), 7);
+    let ordering = analysis.sort();
+    assert_eq!(ordering.iter().map(|node| { node.id() }).collect::<Vec<u32>>(), analysis.order());
+    for (position, node) in ordering.iter().enumerate() {
+        assert!(node.parents().all(|ancestor| { analysis.order()[..position].contains(&ancestor) }));
+    };
+    let Some(lengths) = analysis.shortest_and_longest_paths(&0) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&5], (Some(2), Some(3)));
+    assert_eq!(lengths[&4], (Some(2), Some(2)));
+    assert_eq!(lengths[&6], (None, None)); // unreachable from the source.
+    let Some(lengths) = analysis.shortest_and_longest_paths(&1) else { // sources with incoming edges are accepted.
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&0], (None, None));
+    assert_eq!(lengths[&5], (Some(2), Some(2)));
+    assert_eq!(analysis.shortest_and_longest_paths(&7), None);
+    let topology = Topology::from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    assert_eq!(Some(analysis.bfs_all_paths(&0)), Topology::bfs_all_paths(&topology, 0));
+    // Inconsistent lists build no context, and cycles are reported.
+    let Ok(None) = Analysis::new(&[Node::new(1, Some(0), None, ())]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    let Err(TopologicalError::Cycle { cycle, .. }) = Analysis::new(&[Node::new(0, Some(1), None, ()), Node::new(1, Some(0), None, ())]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(cycle.len(), 2);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_analyses_match_sequential_ones() {
+    use rand::{
+        Rng,
+        SeedableRng,
+        seq::SliceRandom,
+    };
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(3);
+    let mut node_list: Vec<NaryNode<u32, u8>> = (0..2_000_u32)
+        .map(|id| {
+            let parents: Vec<u32> = if id < 5 { Vec::new() } else { (0..rng.gen_range(1..4)).map(|_| { rng.gen_range(0..id) }).collect() };
+            NaryNode::new(id, parents, 0)
+        })
+        .collect();
+    node_list.shuffle(&mut rng);
+    let compare_topologies = |node_list: &[NaryNode<u32, u8>]| {
+        let sequential = Topology::from_nodes(node_list);
+        let parallel = Topology::parallel_from_nodes(node_list);
+        assert_eq!(sequential.all_nodes, parallel.all_nodes);
+        assert_eq!(sequential.collitions, parallel.collitions);
+        assert_eq!(sequential.repeated_nodes, parallel.repeated_nodes);
+        assert_eq!(sequential.outgoing_edges, parallel.outgoing_edges);
+        assert_eq!(sequential.unique_ids, parallel.unique_ids);
+        assert_eq!(sequential.unique_nodes.len(), parallel.unique_nodes.len());
+        for (id, node) in sequential.unique_nodes.iter() {
+            assert!(CollidingNode(node.clone()).has_same_fields_to(&parallel.unique_nodes[id]));
+        };
+        sequential
+    };
+    let topology = compare_topologies(&node_list);
+    let (ordering, level_ends) = topology.topological_order(&node_list).expect("Invalid topological assumptions for this test data.");
+    assert_eq!(topology.parallel_topological_order(&node_list).expect("Invalid topological assumptions for this test data."), (ordering.clone(), level_ends.clone()));
+    for source in [ordering[0], ordering[ordering.len() / 2], ordering[ordering.len() - 1]] {
+        assert_eq!(topology.path_lengths(&ordering, source), topology.parallel_path_lengths(&ordering, &level_ends, source));
+    };
+    let source = ordering[ordering.len() - 40]; // a late node, so that its paths can be listed.
+    assert_eq!(topology.all_paths(source), topology.parallel_all_paths(source));
+    // The parallel entry points give the results of the sequential ones.
+    let ids = |nodes: Vec<NaryNode<u32, u8>>| { nodes.into_iter().map(|node| { node.id }).collect::<Vec<u32>>() };
+    let sorted = Topology::sort(&node_list).expect("Invalid topological assumptions for this test data.").map(ids);
+    assert_eq!(Topology::par_sort(&node_list).expect("Invalid topological assumptions for this test data.").map(ids), sorted);
+    let Ok(Some(levels)) = Topology::par_sort_levels(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(levels.levels.len(), level_ends.len());
+    assert_eq!(Topology::par_bfs_all_paths(&topology, source), Topology::bfs_all_paths(&topology, source));
+    assert!(Topology::par_try_from_slice(&node_list).is_ok());
+    let (Ok(Some(analysis)), Ok(Some(parallel_analysis))) = (Analysis::new(&node_list), Analysis::par_new(&node_list)) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(analysis.order(), parallel_analysis.order());
+    assert_eq!(analysis.shortest_and_longest_paths(&source), parallel_analysis.par_shortest_and_longest_paths(&source));
+    assert_eq!(analysis.bfs_all_paths(&source), parallel_analysis.par_bfs_all_paths(&source));
+    // Duplicates and collitions are detected as the sequential insertion does.
+    let mut inconsistent_list = node_list.clone();
+    for node in node_list.iter().take(50) {
+        inconsistent_list.insert(rng.gen_range(0..inconsistent_list.len()), node.clone());
+        inconsistent_list.insert(rng.gen_range(0..inconsistent_list.len()), NaryNode::new(node.id, node.parents.clone(), rng.gen_range(1..3)));
+    };
+    assert!(!compare_topologies(&inconsistent_list).is_consistent());
+    // Cycles are reported as the sequential sort does.
+    let first_root = node_list.iter().position(|node| { node.parents.is_empty() }).expect("Invalid value assumption.");
+    node_list[first_root].parents.push(ordering[ordering.len() - 1]);
+    let topology = compare_topologies(&node_list);
+    let Err(TopologicalError::Cycle { cycle, unordered }) = topology.topological_order(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    let Err(TopologicalError::Cycle { cycle: parallel_cycle, unordered: parallel_unordered }) = topology.parallel_topological_order(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!((cycle, unordered), (parallel_cycle, parallel_unordered));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel_feature_keeps_sequential_analyses_of_unshareable_nodes() {
+    use std::rc::Rc;
+    let node_list: Vec<Node<u32, Rc<u8>>> = vec![
+        Node::new(0, None, None, Rc::new(0)),
+        Node::new(1, Some(0), None, Rc::new(1)),
+    ];
+    let Ok(Some(analysis)) = Analysis::new(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(analysis.order(), [0, 1]);
+}
+
+#[test]
+fn prioritized_sorts_break_ties_reproducibly() {
+    use rand::{
+        SeedableRng,
+        seq::SliceRandom,
+    };
+    // Payloads are deadlines.
+    let node_list = [
+        Node::new(4, Some(0), None, 9),
+        Node::new(2, None, None, 3),
+        Node::new(0, None, None, 7),
+        Node::new(3, Some(2), Some(0), 1),
+        Node::new(1, Some(2), None, 8),
+    ];
+    let ids = |ordering: Vec<Node<u32, u8>>| { ordering.into_iter().map(|node| { node.id }).collect::<Vec<u32>>() };
+    let Ok(Some(ordering)) = Topology::sort_by_id(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(ids(ordering), vec![0, 2, 1, 3, 4]);
+    let Ok(Some(ordering)) = Topology::sort_by_position(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(ids(ordering), vec![2, 0, 4, 3, 1]);
+    let Ok(Some(ordering)) = Topology::sort_by_priority(&node_list, |deadline| { *deadline }) else { // earliest deadline first.
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(ids(ordering), vec![2, 0, 3, 1, 4]);
+    let Ok(Some(ordering)) = Topology::sort_by_payload(&node_list, |left, right| { right.cmp(left) }) else { // latest deadline first.
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(ids(ordering), vec![0, 4, 2, 1, 3]);
+    let Ok(Some(ordering)) = Topology::sort_by_priority(&node_list, |_| { 0 }) else { // ties keep the list order.
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(ids(ordering), vec![2, 0, 4, 3, 1]);
+    // The order by id doesn't depend on the order of the list.
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(5);
+    let mut shuffled_list = node_list;
+    for _ in 0..10 {
+        shuffled_list.shuffle(&mut rng);
+        let Ok(Some(ordering)) = Topology::sort_by_id(&shuffled_list) else {
+            panic!("Invalid topological assumptions for this test data.")
+        };
+        assert_eq!(ids(ordering), vec![0, 2, 1, 3, 4]);
+    };
+    // Inconsistent lists and cycles are reported as `sort` does.
+    assert!(matches!(Topology::sort_by_id(&[Node::new(1, Some(0), None, 0)]), Ok(None)));
+    let Err(TopologicalError::Cycle { cycle, unordered }) = Topology::sort_by_id(&[Node::new(0, Some(1), None, 0), Node::new(1, Some(0), None, 0), Node::new(2, None, None, 0)]) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(cycle.len(), 2);
+    assert_eq!(unordered, vec![0, 1]);
+}
+
+#[test]
+fn levels_are_antichains_of_longest_path_lengths() {
+    let node_list = [
+        Node::new(5, Some(3), Some(4), ()),
+        Node::new(0, None, None, ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(2, None, Some(0), ()),
+        Node::new(3, Some(0), Some(1), ()),
+        Node::new(4, Some(2), Some(1), ()),
+        Node::new(6, None, None, ()),
+    ];
+    let Ok(Some(levels)) = Topology::sort_levels(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    let ids: Vec<Vec<u32>> = levels.levels.iter().map(|level| { level.iter().map(|node| { node.id }).collect() }).collect();
+    assert_eq!(ids, vec![vec![0, 6], vec![1, 2], vec![3, 4], vec![5]]);
+    assert_eq!(levels.widths(), vec![2, 2, 2, 1]);
+    assert_eq!(levels.level_of(&4), Some(2));
+    assert_eq!(levels.level_of(&7), None);
+    for (level_index, level) in levels.levels.iter().enumerate() {
+        for node in level {
+            assert_eq!(levels.level_indices[&node.id], level_index);
+            // Nodes only reference nodes of previous levels, and at least one of the previous level.
+            assert!(node.parents().all(|ancestor| { levels.level_indices[&ancestor] < level_index }));
+            assert!(level_index == 0 || node.parents().any(|ancestor| { levels.level_indices[&ancestor] == level_index - 1 }));
+        };
+    };
+    let Ok(Some(analysis)) = Analysis::new(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(analysis.levels(), ids.iter().map(|level| { level.as_slice() }).collect::<Vec<&[u32]>>());
+    assert_eq!(analysis.level_indices(), levels.level_indices);
+    assert!(matches!(Topology::sort_levels(&[Node::new(1, Some(0), None, ())]), Ok(None)));
+    assert!(matches!(Topology::sort_levels(&[Node::new(0, Some(1), None, ()), Node::new(1, Some(0), None, ())]), Err(TopologicalError::Cycle { .. })));
+}
+
+#[test]
+fn weighted_paths_follow_edge_and_payload_weights() {
+    use std::time::Duration;
+    // Payloads are task durations in seconds.
+    let node_list = [
+        Node::new(0, None, None, 0),
+        Node::new(1, Some(0), None, 4),
+        Node::new(2, None, Some(0), 1),
+        Node::new(3, Some(0), Some(1), 2),
+        Node::new(4, Some(2), Some(1), 3),
+        Node::new(5, Some(3), Some(4), 5),
+        Node::new(6, None, None, 7),
+    ];
+    let Ok(Some(analysis)) = Analysis::new(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    // Unit weights give the unweighted lengths.
+    let Some(lengths) = analysis.weighted_shortest_and_longest_paths(&0, 0, |_, _, _| { 1_usize }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(Some(lengths), analysis.shortest_and_longest_paths(&0));
+    // Right branches cost ten times more than left ones.
+    let Some(lengths) = analysis.weighted_shortest_and_longest_paths(&0, 0.0, |_, _, side| { if side == Side::Left { 0.5 } else { 5.0 } }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&0], (Some(0.0), Some(0.0)));
+    assert_eq!(lengths[&3], (Some(0.5), Some(5.5)));
+    assert_eq!(lengths[&4], (Some(5.5), Some(5.5)));
+    assert_eq!(lengths[&5], (Some(1.0), Some(10.5)));
+    assert_eq!(lengths[&6], (None, None));
+    let Some(lengths) = analysis.weighted_shortest_and_longest_paths(&1, 0, |parent, child, _| { parent + child }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&5], (Some(12), Some(14)));
+    assert_eq!(lengths[&2], (None, None));
+    // The longest path by durations is the critical path of the tasks.
+    let Some(lengths) = analysis.payload_weighted_shortest_and_longest_paths(&0, Duration::ZERO, |seconds| { Duration::from_secs(*seconds) }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&5], (Some(Duration::from_secs(7)), Some(Duration::from_secs(12))));
+    assert_eq!(analysis.payload_weighted_shortest_and_longest_paths(&7, 0, |seconds| { *seconds }), None);
+    // Weights need not be `Copy`.
+    #[derive(Debug, Clone, PartialEq, PartialOrd)]
+    struct Seconds(Box<u64>);
+    impl core::ops::Add for Seconds {
+        type Output = Seconds;
+        fn add(self, other: Seconds) -> Seconds {
+            Seconds(Box::new(*self.0 + *other.0))
+        }
+    }
+    let Some(lengths) = analysis.payload_weighted_shortest_and_longest_paths(&0, Seconds(Box::new(0)), |seconds| { Seconds(Box::new(*seconds)) }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&5], (Some(Seconds(Box::new(7))), Some(Seconds(Box::new(12)))));
+    // NaN sums are ignored whatever the order of the edges.
+    let Some(lengths) = analysis.weighted_shortest_and_longest_paths(&0, 0.0, |parent, _, _| { if parent == 1 { f64::NAN } else { 1.0 } }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&3], (Some(1.0), Some(1.0)));
+    assert_eq!(lengths[&4], (Some(2.0), Some(2.0)));
+    assert_eq!(lengths[&5], (Some(2.0), Some(3.0)));
+    let Some(lengths) = analysis.weighted_shortest_and_longest_paths(&1, 0.0, |_, _, _| { f64::NAN }) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&1], (Some(0.0), Some(0.0)));
+    assert_eq!(lengths[&3], (None, None));
+}
@@ -0,0 +1,184 @@
+use core::hash::Hash;
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+use crate::{
+    DagNode,
+    Side,
+};
+
+/// A reference from a node to an ancestor id that is not defined in the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DanglingReference<T> {
+    pub id: T,
+    pub side: Side,
+    pub missing: T,
+}
+
+/// `ValidationReport<T>` lists every issue found in a list of nodes, by the ids involved.
+/// Dangling references, repeated ids and collitions make the list inconsistent,
+/// whereas self-references and duplicate parents (i.e. `left == right`) are only informative.
+/// Ids are listed in the order they first appear in the list, once per issue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationReport<T> {
+    pub dangling_references: Vec<DanglingReference<T>>,
+    pub repeated_ids: Vec<T>,
+    pub collitions: Vec<T>,
+    pub self_references: Vec<T>,
+    pub duplicate_parents: Vec<T>,
+}
+
+impl<T> ValidationReport<T> {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        ValidationReport {
+            dangling_references: Vec::new(),
+            repeated_ids: Vec::new(),
+            collitions: Vec::new(),
+            self_references: Vec::new(),
+            duplicate_parents: Vec::new(),
+        }
+    }
+    /// Returns `true` if there are no dangling references, no repeated ids and no collitions.
+    pub fn is_consistent(&self) -> bool {
+        self.dangling_references.is_empty()
+        && self.repeated_ids.is_empty()
+        && self.collitions.is_empty()
+    }
+    /// Returns `true` if the report has no issues at all.
+    pub fn is_empty(&self) -> bool {
+        self.is_consistent()
+        && self.self_references.is_empty()
+        && self.duplicate_parents.is_empty()
+    }
+}
+
+impl<T> Default for ValidationReport<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Index of a list of nodes by id, classifying the nodes as `Topology` does when inserting them in order:
+/// a node equal in all fields to a previous one repeats its id, and the first occurrence of a different node with a known id collides.
+/// Nodes are only borrowed, so borrowed layouts can be validated without copying them.
+pub(crate) struct SliceIndex<T> {
+    pub positions: HashMap<T, usize>, // position of the first node with each id.
+    pub unique_positions: Vec<usize>, // position of the first node with each id, in ascending order.
+    pub repeated_ids: HashSet<T>,
+    pub colliding_ids: HashSet<T>,
+}
+
+impl<T: Eq + Hash + Copy> SliceIndex<T> {
+    pub fn new<N: DagNode<Id = T>>(node_list: &[N]) -> Self {
+        let mut positions: HashMap<T, usize> = HashMap::with_capacity(node_list.len());
+        let mut variants: HashMap<T, Vec<usize>> = HashMap::new(); // positions of the different nodes of the ids found more than once.
+        let mut unique_positions: Vec<usize> = Vec::with_capacity(node_list.len());
+        let mut repeated_ids: HashSet<T> = HashSet::new();
+        let mut colliding_ids: HashSet<T> = HashSet::new();
+        for (position, node) in node_list.iter().enumerate() {
+            let id = node.id();
+            let Some(first_position) = positions.get(&id) else {
+                positions.insert(id, position);
+                unique_positions.push(position);
+                continue;
+            };
+            let id_variants = variants.entry(id).or_insert_with(|| { vec![*first_position] });
+            let is_repeated = id_variants.iter().any(|variant| {
+                let variant_node = &node_list[*variant];
+                variant_node.references().eq(node.references()) && variant_node.payload_eq(node)
+            });
+            if is_repeated {
+                repeated_ids.insert(id);
+            } else {
+                id_variants.push(position);
+                colliding_ids.insert(id);
+            };
+        };
+        SliceIndex {
+            positions,
+            unique_positions,
+            repeated_ids,
+            colliding_ids,
+        }
+    }
+    /// Lists the issues of the indexed list, as `Topology::validate` does.
+    pub fn report<N: DagNode<Id = T>>(&self, node_list: &[N]) -> ValidationReport<T> {
+        report(
+            self.unique_positions.iter().map(|position| { &node_list[*position] }),
+            |id| { self.positions.contains_key(id) },
+            &self.repeated_ids,
+            &self.colliding_ids,
+        )
+    }
+}
+
+/// Lists the issues of a list of nodes, given its unique nodes in the order they were inserted and the ids found repeated or colliding, see `Topology::validate`.
+pub(crate) fn report<'a, N: DagNode + 'a>(
+    unique_nodes: impl Iterator<Item = &'a N>,
+    contains_id: impl Fn(&N::Id) -> bool,
+    repeated_ids: &HashSet<N::Id>,
+    colliding_ids: &HashSet<N::Id>,
+) -> ValidationReport<N::Id> {
+    let mut report = ValidationReport::new();
+    for node in unique_nodes {
+        let id = node.id();
+        let mut parents: HashSet<N::Id> = HashSet::new();
+        let mut has_duplicate_parents = false;
+        for (side, ancestor) in node.references() {
+            if !contains_id(&ancestor) {
+                report.dangling_references.push(DanglingReference { id, side, missing: ancestor });
+            };
+            has_duplicate_parents |= !parents.insert(ancestor);
+        };
+        if has_duplicate_parents {
+            report.duplicate_parents.push(id);
+        };
+        if parents.contains(&id) {
+            report.self_references.push(id);
+        };
+        if repeated_ids.contains(&id) {
+            report.repeated_ids.push(id);
+        };
+        if colliding_ids.contains(&id) {
+            report.collitions.push(id);
+        };
+    };
+    report
+}
+
+/// `SafetyReport<T>` lists the conditions that make a `Dag` unsafe, by the ids involved.
+/// Unresolved references and cycles make the dag unsafe, whereas overwritten ids are only informative.
+/// The report is kept up to date as the dag changes, so the dag becomes safe again once the issues are solved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafetyReport<T> {
+    /// References from nodes in the dag to ids that are not present.
+    pub unresolved_references: Vec<DanglingReference<T>>,
+    /// Ids present in the dag whose node was replaced by `Dag::insert_or_update`, in the order they were first overwritten.
+    pub overwritten_ids: Vec<T>,
+    /// Cycles closed by the arrival of a missing reference, each in edge order as in `TopologicalError::Cycle`.
+    pub cycles: Vec<Vec<T>>,
+}
+
+impl<T> SafetyReport<T> {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        SafetyReport {
+            unresolved_references: Vec::new(),
+            overwritten_ids: Vec::new(),
+            cycles: Vec::new(),
+        }
+    }
+    /// Returns `true` if there are no unresolved references and no cycles.
+    pub fn is_safe(&self) -> bool {
+        self.unresolved_references.is_empty()
+        && self.cycles.is_empty()
+    }
+}
+
+impl<T> Default for SafetyReport<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
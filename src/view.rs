@@ -0,0 +1,135 @@
+use core::fmt::Debug;
+use std::collections::HashMap;
+use crate::{
+    Node,
+    DagNode,
+    indexed,
+    error::TopologicalError,
+    validation::{
+        DanglingReference,
+        SliceIndex,
+        ValidationReport,
+    },
+};
+
+/// `TopologyView<'a, N>` is a borrowed analysis view over a slice of nodes.
+/// Unlike `Topology`, it doesn't copy the nodes: they are indexed by their position in the slice, and results are given as positions or references.
+#[derive(Debug, Clone)]
+pub struct TopologyView<'a, N: DagNode> {
+    nodes: &'a [N],
+    positions: HashMap<N::Id, usize>, // position of the node with each id.
+    outgoing_edges: Vec<Vec<usize>>, // positions of the nodes referencing each position, once per reference.
+}
+
+impl<'a, N: DagNode + Debug> TopologyView<'a, N> {
+    /// Constructs a view from a slice of nodes if a consistent DAG topology can be constructed, see `Topology::from_slice`.
+    pub fn from_slice(node_list: &'a [N]) -> Option<Self> {
+        Self::try_from_slice(node_list).ok()
+    }
+    /// Constructs a view from a slice of nodes.
+    /// The slice is validated as `Topology::try_from_slice` does, so if a consistent DAG topology can't be constructed it returns the same `ValidationReport`.
+    pub fn try_from_slice(node_list: &'a [N]) -> Result<Self, ValidationReport<N::Id>> {
+        let slice_index = SliceIndex::new(node_list);
+        let report = slice_index.report(node_list);
+        if !report.is_consistent() {
+            return Err(report);
+        };
+        // a consistent slice has no repeated ids, so every position holds a unique node.
+        let mut outgoing_edges: Vec<Vec<usize>> = vec![Vec::new(); node_list.len()];
+        for (position, node) in node_list.iter().enumerate() {
+            for ancestor in node.parents() {
+                outgoing_edges[slice_index.positions[&ancestor]].push(position); // pushed in slice order.
+            };
+        };
+        Ok(TopologyView {
+            nodes: node_list,
+            positions: slice_index.positions,
+            outgoing_edges,
+        })
+    }
+    /// Number of unique nodes.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+    /// Returns `true` if the view has no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    /// Gets the position in the slice of the node with the given id.
+    pub fn position(&self, id: &N::Id) -> Option<usize> {
+        self.positions.get(id).copied()
+    }
+    /// Gets a reference to the node with the given id.
+    pub fn get(&self, id: &N::Id) -> Option<&'a N> {
+        self.positions.get(id).map(|position| { &self.nodes[*position] })
+    }
+    /// Sorts the positions of the nodes in a topological order with Kahn's algorithm, in O(V + E).
+    /// If the view has a cycle it returns a `TopologicalError::Cycle` with one of its cycles and all the nodes that could not be ordered.
+    pub fn sort_positions(&self) -> Result<Vec<usize>, TopologicalError<N::Id>> {
+        indexed::sort(
+            self.nodes.len(),
+            |position| { self.nodes[position].parents().map(|ancestor| { self.positions[&ancestor] }) },
+            |position| { self.outgoing_edges[position].iter().copied() },
+        )
+        .map_err(|unsorted| {
+            TopologicalError::Cycle {
+                cycle: unsorted.cycle.into_iter().map(|position| { self.nodes[position].id() }).collect(),
+                unordered: unsorted.unordered.into_iter().map(|position| { self.nodes[position].id() }).collect(),
+            }
+        })
+    }
+    /// Sorts the nodes in a topological order, by reference, see `sort_positions`.
+    pub fn sort(&self) -> Result<Vec<&'a N>, TopologicalError<N::Id>> {
+        let nodes = self.nodes;
+        Ok(self.sort_positions()?.into_iter().map(|position| { &nodes[position] }).collect())
+    }
+}
+
+#[test]
+fn view_sorts_by_reference() {
+    let node_list = [
+        Node::new(5, Some(3), Some(4), ()),
+        Node::new(4, Some(2), Some(1), ()),
+        Node::new(3, Some(0), Some(1), ()),
+        Node::new(2, None, Some(0), ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(0, None, None, ()),
+    ];
+    let view = TopologyView::from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    assert_eq!(view.len(), 6);
+    assert_eq!(view.position(&0), Some(5));
+    assert!(core::ptr::eq(view.get(&3).expect("Invalid value assumption."), &node_list[2]));
+    let Ok(positions) = view.sort_positions() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(positions, vec![5, 3, 4, 1, 2, 0]);
+    let Ok(ordering) = view.sort() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    for (node, position) in ordering.into_iter().zip(positions) {
+        assert!(core::ptr::eq(node, &node_list[position]));
+    };
+}
+
+#[test]
+fn view_reports_invalid_lists_and_cycles() {
+    let node_list = [Node::new(0, None, None, ()), Node::new(1, Some(2), None, ()), Node::new(0, Some(1), None, ())];
+    let Err(report) = TopologyView::try_from_slice(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(report.dangling_references, vec![DanglingReference { id: 1, side: crate::Side::Left, missing: 2 }]);
+    assert!(report.repeated_ids.is_empty());
+    assert_eq!(report.collitions, vec![0]);
+    let node_list = [Node::new(0, None, None, ()), Node::new(1, Some(0), None, ()), Node::new(1, Some(0), None, ())];
+    let Err(report) = TopologyView::try_from_slice(&node_list) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(Some(report), crate::topological::Topology::try_from_slice(&node_list).err()); // exact duplicates are repeated ids, as in `Topology`.
+    let node_list = [Node::new(0, Some(2), None, ()), Node::new(1, Some(0), None, ()), Node::new(2, Some(1), None, ()), Node::new(3, None, None, ())];
+    let view = TopologyView::from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    let Err(TopologicalError::Cycle { cycle, unordered }) = view.sort() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(cycle, vec![1, 2, 0]);
+    assert_eq!(unordered, vec![0, 1, 2]);
+}
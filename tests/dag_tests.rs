@@ -1,18 +1,54 @@
-use std::collections::{
-    HashSet,
-    // HashMap,
+use std::{
+    collections::{
+        HashSet,
+        // HashMap,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
 };
 use custom_dag::{
     Node, 
+    NaryNode,
+    DagNode,
     Dag,
-    collitions::CollidingNode,
+    RemovalPolicy,
+    error::{
+        RemovalError,
+        InsertError,
+        UpdateError,
+    },
+    collitions::{
+        CollidingNode,
+        CollitionPolicy,
+        CollitionDecision,
+        Resolution,
+    },
+    interner::{
+        InternedDag,
+        KeyedNode,
+    },
+    pending::{
+        Attachment,
+        PendingLimits,
+    },
+    validation::DanglingReference,
+    equivocation::{
+        Equivocation,
+        FieldDiff,
+    },
+    Side,
+    topological::Topology,
 };
+#[allow(unused_imports)] // baseline imports, kept as written.
 use rand::{ 
     SeedableRng,
     RngCore,
     prelude::IteratorRandom,
     Rng,
 };
+use rand::seq::SliceRandom;
 
 #[test]
 fn create_1_000_000_unconnected_nodes_dag() {
@@ -27,6 +63,7 @@ fn create_1_000_000_unconnected_nodes_dag() {
 }
 
 #[test]
+#[allow(clippy::useless_conversion)] // baseline test, kept as written.
 fn insert_existing_node_id_does_not_update() {
     type TestType = u32;
     let id: TestType = 0;
@@ -64,7 +101,7 @@ fn insert_existing_node_id_does_not_update() {
         node_b.right,
     );
     // Examine the DAG's collition collection.
-    let collitions: &HashSet<CollidingNode<TestType, ()>> = dag.get_collitions(&id).expect("Invalid value assumption.");
+    let collitions: &HashSet<CollidingNode<Node<TestType, ()>>> = dag.get_collitions(&id).expect("Invalid value assumption.");
     let colliding_node = collitions.get(&CollidingNode::from(node_b)).expect("Invalid value assumption.");
     // The colliding node corresponds to node_b.
     assert_eq!(colliding_node, &CollidingNode::from(node_b));
@@ -93,7 +130,7 @@ fn insert_existing_node_marks_dag_unsafe() {
     // Trying to insert a node with the id of an already inserted node collects the collition and returns the value of the previously inserted node, which will persist in the DAG.
     assert!(dag.insert(node_b).expect("Invalid value assumption.").has_same_fields_to(&node_a));
     // Examine the DAG's collition collection.
-    let collitions: &HashSet<CollidingNode<TestType, ()>> = dag.get_collitions(&id).expect("Invalid value assumption.");
+    let collitions: &HashSet<CollidingNode<Node<TestType, ()>>> = dag.get_collitions(&id).expect("Invalid value assumption.");
     let colliding_node = collitions.get(&CollidingNode::from(node_b)).expect("Invalid value assumption.");
     assert_eq!(colliding_node, &CollidingNode::from(node_b));
     assert_eq!(colliding_node, &CollidingNode::from(node_b));
@@ -104,7 +141,7 @@ fn insert_existing_node_marks_dag_unsafe() {
 
     // insert_or_update updates the dag values for the node id 0 and returns the previous values, node_a. 
     // The final fields values for the node id 0 in the dag are equal to node_b but not node_a.
-    assert!(dag.insert_or_update(node_b).expect("Invalid value assumption.").has_same_fields_to(&node_a));
+    assert!(dag.insert_or_update(node_b).expect("Invalid value assumption.").expect("Invalid value assumption.").has_same_fields_to(&node_a));
     assert!(!dag.get(&node_a.id).expect("Invalid value assumption.").has_same_fields_to(&node_a));
     assert!(dag.get(&node_a.id).expect("Invalid value assumption.").has_same_fields_to(&node_b));
 
@@ -112,6 +149,7 @@ fn insert_existing_node_marks_dag_unsafe() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)] // baseline test, kept as written.
 fn insert_a_node_with_non_existent_left_reference_marks_dag_unsafe() {
     type TestType = u32;
     let id: TestType = 0;
@@ -122,6 +160,7 @@ fn insert_a_node_with_non_existent_left_reference_marks_dag_unsafe() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)] // baseline test, kept as written.
 fn insert_a_node_with_non_existent_right_reference_marks_dag_unsafe() {
     type TestType = u32;
     let id: TestType = 0;
@@ -166,6 +205,723 @@ fn insert_dag_from_list() {
 }
 
 #[test]
+fn children_index_follows_insertions_and_updates() {
+    let node_a = Node::new(0,None,None,());
+    let node_b = Node::new(1,Some(0),None,());
+    let node_c = Node::new(2,Some(0),Some(0),());
+    let node_d = Node::new(3,Some(1), Some(5), ());
+    let mut dag = Dag::new();
+    dag.insert_from(&[node_a, node_b, node_c, node_d]);
+    let mut children_of_a = dag.children(&0).to_vec();
+    children_of_a.sort();
+    assert_eq!(children_of_a, vec![1, 2, 2]); // node_c references node_a twice.
+    assert_eq!(dag.out_degree(&0), 3);
+    assert_eq!(dag.children(&1), &[3]);
+    assert_eq!(dag.children(&5), &[3]); // node_d waits for the missing node 5.
+    assert_eq!(dag.parents(&3), Some(vec![1, 5]));
+    assert_eq!(dag.parents(&5), None);
+    assert!(dag.is_tip(&2));
+    assert!(dag.is_tip(&3));
+    assert!(!dag.is_tip(&1));
+    assert!(!dag.is_tip(&5)); // missing nodes are not tips.
+    // Re-parenting node_d moves it from the children of node_b and 5 to the children of node_c.
+    assert!(dag.insert_or_update(Node::new(3, Some(2), None, ())).is_ok());
+    assert_eq!(dag.children(&2), &[3]);
+    assert!(dag.children(&5).is_empty());
+    assert!(dag.is_tip(&1));
+    assert!(!dag.is_tip(&2));
+    assert_eq!(dag.parents(&3), Some(vec![2]));
+    // A colliding node is not inserted, so it doesn't change the index.
+    dag.insert(Node::new(4, Some(1), None, ()));
+    dag.insert(Node::new(4, Some(2), None, ()));
+    assert_eq!(dag.children(&1), &[4]);
+    assert_eq!(dag.children(&2), &[3]);
+}
+
+#[test]
+fn try_insert_rejects_invalid_nodes() {
+    let node_a = Node::new(0,None,None,());
+    let node_b = Node::new(1,Some(0),None,());
+    let mut dag = Dag::new();
+    assert_eq!(dag.try_insert(node_a), Ok(()));
+    assert_eq!(dag.try_insert(node_b), Ok(()));
+    assert_eq!(dag.try_insert(node_b), Err(InsertError::DuplicateId(1)));
+    assert_eq!(dag.try_insert(Node::new(1, None, None, ())), Err(InsertError::Collition(1)));
+    assert_eq!(dag.try_insert(Node::new(2, Some(5), Some(0), ())), Err(InsertError::MissingLeftParent { id: 2, missing: 5 }));
+    assert_eq!(dag.try_insert(Node::new(2, Some(0), Some(6), ())), Err(InsertError::MissingRightParent { id: 2, missing: 6 }));
+    assert_eq!(dag.try_insert(Node::new(2, None, Some(6), ())), Err(InsertError::MissingRightParent { id: 2, missing: 6 }));
+    // Rejected nodes are neither inserted nor collected.
+    assert!(!dag.contains_id(&2));
+    assert!(dag.get_collitions(&1).is_none());
+    assert!(dag.children(&6).is_empty());
+    assert!(dag.get(&1).expect("Invalid value assumption.").has_same_fields_to(&node_b));
+    assert!(dag.is_safe());
+    assert_eq!(dag.try_insert(Node::new(2, Some(0), Some(1), ())), Ok(()));
+    assert_eq!(dag.children(&1), &[2]);
+}
+
+#[test]
+fn try_insert_nary_nodes_reports_missing_position() {
+    let mut dag = Dag::new();
+    assert_eq!(dag.try_insert(NaryNode::new(0, vec![], ())), Ok(()));
+    assert_eq!(dag.try_insert(NaryNode::new(1, vec![0, 0, 3], ())), Err(InsertError::MissingParent { id: 1, position: 2, missing: 3 }));
+    assert!(dag.is_safe());
+}
+
+fn removal_test_dag() -> Dag<Node<u32, ()>> {
+    let node_a = Node::new(0,None,None,());
+    let node_b = Node::new(1,Some(0),None,());
+    let node_c = Node::new(2,None,Some(0),());
+    let node_d = Node::new(3,Some(1), Some(2), ());
+    let node_e = Node::new(4,Some(0), None, ());
+    let mut dag = Dag::new();
+    dag.insert_from(&[node_a, node_b, node_c, node_d, node_e]);
+    dag
+}
+
+#[test]
+fn remove_refuses_nodes_with_descendants() {
+    let mut dag = removal_test_dag();
+    assert_eq!(dag.remove(&7, RemovalPolicy::Refuse).err(), Some(RemovalError::NotFound(7)));
+    assert_eq!(dag.remove(&1, RemovalPolicy::Refuse).err(), Some(RemovalError::HasDescendants { id: 1, children: vec![3] }));
+    assert!(dag.contains_id(&1));
+    dag.insert(Node::new(4, Some(3), None, ())); // collition for a tip.
+    let removal = dag.remove(&4, RemovalPolicy::Refuse).expect("Invalid value assumption.");
+    assert_eq!(removal.removed, vec![Node::new(4, Some(0), None, ())]);
+    assert!(removal.orphans.is_empty());
+    assert!(!dag.contains_id(&4));
+    assert!(dag.get_collitions(&4).is_none());
+    assert_eq!(dag.children(&0).len(), 2);
+    assert!(dag.is_safe());
+}
+
+#[test]
+fn remove_cascades_to_descendants() {
+    let mut dag = removal_test_dag();
+    let removal = dag.remove(&1, RemovalPolicy::Cascade).expect("Invalid value assumption.");
+    let removed_ids: Vec<u32> = removal.removed.iter().map(|node| { node.id }).collect();
+    assert_eq!(removed_ids, vec![1, 3]);
+    assert!(removal.orphans.is_empty());
+    assert!(dag.children(&2).is_empty());
+    assert!(dag.is_tip(&2));
+    assert!(dag.is_safe());
+    let removal = dag.remove(&0, RemovalPolicy::Cascade).expect("Invalid value assumption.");
+    assert_eq!(removal.removed.len(), 3);
+    assert!(!dag.contains_id(&2) && !dag.contains_id(&4));
+}
+
+#[test]
+fn remove_leaves_orphans() {
+    let mut dag = removal_test_dag();
+    let removal = dag.remove(&0, RemovalPolicy::Orphan).expect("Invalid value assumption.");
+    assert_eq!(removal.removed.len(), 1);
+    let mut orphans = removal.orphans.clone();
+    orphans.sort();
+    assert_eq!(orphans, vec![1, 2, 4]);
+    assert!(dag.contains_id(&1) && dag.contains_id(&2) && dag.contains_id(&4));
+    assert_eq!(dag.children(&0).len(), 3); // orphans still reference the removed node.
+    assert!(!dag.is_safe());
+}
+
+#[test]
+fn insert_nary_nodes_with_existent_references_is_safe() {
+    let node_a = NaryNode::new(0, vec![], ());
+    let node_b = NaryNode::new(1, vec![0], ());
+    let node_c = NaryNode::new(2, vec![0, 1], ());
+    let node_d = NaryNode::new(3, vec![0, 1, 2], ());
+    let mut dag = Dag::new();
+    for result in dag.insert_from(&[node_a, node_b, node_c, node_d.clone()]) {
+        assert_eq!(result, None)
+    };
+    assert!(dag.is_safe());
+    assert_eq!(dag.get(&3).expect("Invalid value assumption.").parents, vec![0, 1, 2]);
+    // A third parent that is not in the DAG makes it unsafe, as left or right references do for binary nodes.
+    assert!(dag.insert(NaryNode::new(4, vec![0, 1, 5], ())).is_some());
+    assert!(!dag.is_safe());
+}
+
+#[test]
+fn insert_nodes_with_owned_payloads() {
+    let node_a = Node::new(0, None, None, String::from("genesis"));
+    let node_b = Node::new(1, Some(0), None, String::from("first"));
+    let node_c = Node::new(1, Some(0), None, String::from("forged"));
+    let mut dag = Dag::new();
+    assert_eq!(dag.insert(node_a.clone()), None);
+    assert_eq!(dag.insert(node_b.clone()), None);
+    assert!(dag.is_safe());
+    // A node with the same id and references but a different payload is a collition.
+    assert!(dag.insert(node_c.clone()).expect("Invalid value assumption.").has_same_fields_to(&node_b));
+    let collitions = dag.get_collitions(&1).expect("Invalid value assumption.");
+    assert!(collitions.contains(&CollidingNode::from(node_c)));
+    assert!(!collitions.contains(&CollidingNode::from(node_b)));
+    assert_eq!(dag.get(&1).expect("Invalid value assumption.").payload, "first");
+}
+
+#[test]
+fn insert_nodes_with_non_hash_payloads() {
+    let node_a = NaryNode::new(0, vec![], vec![0.5_f64]);
+    let node_b = NaryNode::new(1, vec![0], vec![1.5_f64, 2.5]);
+    let node_c = NaryNode::new(1, vec![0], vec![1.5_f64]);
+    let mut dag = Dag::new();
+    for result in dag.insert_from(&[node_a, node_b.clone(), node_c.clone()]) {
+        assert!(result.is_none() || result == Some(node_b.clone()));
+    };
+    let collitions = dag.get_collitions(&1).expect("Invalid value assumption.");
+    assert_eq!(collitions.len(), 1);
+    assert!(collitions.contains(&CollidingNode::from(node_c)));
+}
+
+#[test]
+fn insert_nodes_with_owned_ids() {
+    let genesis = KeyedNode::new(String::from("9fceb02"), vec![], "init");
+    let fix = KeyedNode::new(String::from("1b2e3c4"), vec![String::from("9fceb02")], "fix");
+    let orphan = KeyedNode::new(String::from("deadbee"), vec![String::from("0000000")], "orphan");
+    let mut dag = InternedDag::new();
+    assert_eq!(dag.insert(genesis.clone()), None);
+    assert_eq!(dag.insert(fix.clone()), None);
+    assert!(dag.is_safe());
+    assert!(dag.contains_id(&String::from("1b2e3c4")));
+    assert_eq!(dag.get(&String::from("1b2e3c4")), Some(fix.clone()));
+    // Inserting an existing id returns the present node, reported with its original ids.
+    assert_eq!(dag.insert(KeyedNode::new(String::from("1b2e3c4"), vec![], "rewrite")), Some(fix));
+    // Missing ancestors are interned as well, so they can be resolved back even if they are not in the dag.
+    assert_eq!(dag.insert(orphan.clone()), Some(orphan));
+    assert!(!dag.is_safe());
+    assert!(!dag.contains_id(&String::from("0000000")));
+    assert_eq!(dag.interner().len(), 4);
+}
+
+/// Domain record analyzed directly through the `DagNode` trait.
+#[derive(Debug, Clone)]
+struct Commit {
+    hash: u64,
+    parents: Vec<u64>,
+    message: &'static str,
+}
+
+impl PartialEq for Commit {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl DagNode for Commit {
+    type Id = u64;
+    type Payload = &'static str;
+    fn id(&self) -> u64 {
+        self.hash
+    }
+    fn parents(&self) -> impl Iterator<Item = u64> + '_ {
+        self.parents.iter().copied()
+    }
+    fn payload(&self) -> &&'static str {
+        &self.message
+    }
+}
+
+#[test]
+fn insert_user_defined_nodes() {
+    let root = Commit { hash: 0xa1, parents: vec![], message: "init" };
+    let feature = Commit { hash: 0xb2, parents: vec![0xa1], message: "feature" };
+    let merge = Commit { hash: 0xc3, parents: vec![0xa1, 0xb2], message: "merge" };
+    let rewritten_merge = Commit { hash: 0xc3, parents: vec![0xb2], message: "merge" };
+    let mut dag = Dag::new();
+    for result in dag.insert_from(&[root, feature, merge.clone()]) {
+        assert_eq!(result, None)
+    };
+    assert!(dag.is_safe());
+    assert_eq!(dag.get(&0xc3).expect("Invalid value assumption.").payload(), &"merge");
+    // Inserting a commit with a known hash keeps the first one and collects the newcomer as a collition.
+    assert_eq!(dag.insert(rewritten_merge.clone()), Some(merge.clone()));
+    let collitions: &HashSet<CollidingNode<Commit>> = dag.get_collitions(&0xc3).expect("Invalid value assumption.");
+    let colliding_node = collitions.get(&CollidingNode::from(rewritten_merge.clone())).expect("Invalid value assumption.");
+    assert!(colliding_node.has_same_fields_to(&rewritten_merge));
+    assert!(!colliding_node.has_same_fields_to(&merge));
+}
+
+#[test]
+fn insert_or_hold_promotes_pending_nodes_recursively() {
+    let mut dag = Dag::new();
+    assert_eq!(dag.insert_or_hold(Node::new(3, Some(2), Some(1), ())), Ok(Attachment::Pending { missing: vec![2, 1], evicted: vec![] }));
+    assert_eq!(dag.insert_or_hold(Node::new(2, Some(1), Some(1), ())), Ok(Attachment::Pending { missing: vec![1], evicted: vec![] }));
+    assert_eq!(dag.insert_or_hold(Node::new(4, Some(3), None, ())), Ok(Attachment::Pending { missing: vec![3], evicted: vec![] }));
+    assert_eq!(dag.insert_or_hold(Node::new(2, None, None, ())), Err(InsertError::Collition(2)));
+    assert_eq!(dag.pending().len(), 3);
+    let mut missing_ids = dag.pending().missing_ids();
+    missing_ids.sort();
+    assert_eq!(missing_ids, vec![1, 2, 3]);
+    assert_eq!(dag.pending().waiting_for(&1), &[3, 2]);
+    assert!(dag.pending().waiting_nodes().all(|node| { !dag.contains_id(&node.id) }));
+    assert!(dag.is_safe());
+    assert_eq!(dag.insert_or_hold(Node::new(1, None, None, ())), Ok(Attachment::Attached { attached: vec![1, 2, 3, 4], evicted: vec![] }));
+    assert!(dag.pending().is_empty());
+    assert!(dag.pending().missing_ids().is_empty());
+    assert_eq!(dag.children(&3), &[4]);
+    assert!(dag.is_safe());
+}
+
+#[test]
+fn pending_nodes_are_promoted_by_any_insertion() {
+    let mut dag = Dag::new();
+    assert!(dag.insert_or_hold(Node::new(1, Some(0), None, ())).is_ok());
+    assert!(dag.insert_or_hold(Node::new(2, Some(1), None, ())).is_ok());
+    assert_eq!(dag.insert(Node::new(0, None, None, ())), None);
+    assert!(dag.contains_id(&1) && dag.contains_id(&2));
+    assert!(dag.pending().is_empty());
+    // A pending id inserted meanwhile by other means is collected as a collition when promoted.
+    assert!(dag.insert_or_hold(Node::new(4, Some(3), None, ())).is_ok());
+    assert_eq!(dag.insert(Node::new(4, Some(0), None, ())), None);
+    assert_eq!(dag.try_insert(Node::new(3, None, None, ())), Ok(()));
+    assert!(dag.pending().is_empty());
+    assert_eq!(dag.parents(&4), Some(vec![0]));
+    assert_eq!(dag.get_collitions(&4).map(|collitions| { collitions.len() }), Some(1));
+}
+
+#[test]
+fn pending_pool_respects_size_and_age_limits() {
+    let limits = PendingLimits { max_nodes: Some(2), max_age: Some(Duration::from_secs(10)) };
+    let mut dag = Dag::with_pending_limits(limits);
+    assert_eq!(dag.pending().limits(), limits);
+    assert!(dag.insert_or_hold(Node::new(1, Some(0), None, ())).is_ok());
+    assert!(dag.insert_or_hold(Node::new(2, Some(0), None, ())).is_ok());
+    assert_eq!(dag.insert_or_hold(Node::new(3, Some(0), None, ())), Ok(Attachment::Pending { missing: vec![0], evicted: vec![1] }));
+    assert!(!dag.pending().contains_id(&1));
+    assert_eq!(dag.pending().waiting_for(&0), &[2, 3]);
+    // Evicted ids can be held again.
+    assert_eq!(dag.insert_or_hold(Node::new(1, Some(0), None, ())), Ok(Attachment::Pending { missing: vec![0], evicted: vec![2] }));
+    assert!(dag.expire_pending().is_empty());
+    let expired = dag.expire_pending_at(Instant::now() + Duration::from_secs(10));
+    assert_eq!(expired.iter().map(|node| { node.id }).collect::<Vec<u32>>(), vec![3, 1]);
+    assert!(dag.pending().is_empty());
+    assert!(dag.pending().missing_ids().is_empty());
+    assert_eq!(dag.insert_or_hold(Node::new(0, None, None, ())), Ok(Attachment::Attached { attached: vec![0], evicted: vec![] }));
+}
+
+#[test]
+fn pending_nodes_expire_when_nodes_are_held_or_promoted() {
+    let start = Instant::now();
+    let mut dag = Dag::with_pending_limits(PendingLimits { max_nodes: None, max_age: Some(Duration::from_secs(10)) });
+    assert!(dag.insert_or_hold_at(Node::new(1, Some(0), None, ()), start).is_ok());
+    assert!(dag.insert_or_hold_at(Node::new(2, Some(0), None, ()), start + Duration::from_secs(5)).is_ok());
+    // Holding a node expires the stale ones.
+    assert_eq!(dag.insert_or_hold_at(Node::new(3, Some(4), None, ()), start + Duration::from_secs(10)), Ok(Attachment::Pending { missing: vec![4], evicted: vec![1] }));
+    // Promoting nodes expires the stale ones first, so they are not attached.
+    assert_eq!(
+        dag.insert_or_hold_at(Node::new(0, None, None, ()), start + Duration::from_secs(15)),
+        Ok(Attachment::Attached { attached: vec![0], evicted: vec![2] }),
+    );
+    assert_eq!(dag.get(&2), None);
+    assert_eq!(dag.pending().waiting_for(&4), &[3]);
+    // Other insertion methods expire the stale nodes before promoting.
+    let mut dag = Dag::with_pending_limits(PendingLimits { max_nodes: None, max_age: Some(Duration::from_secs(10)) });
+    let past = Instant::now().checked_sub(Duration::from_secs(20)).expect("Invalid value assumption.");
+    assert!(dag.insert_or_hold_at(Node::new(1, Some(0), None, ()), past).is_ok());
+    assert!(dag.insert_or_hold(Node::new(2, Some(0), None, ())).is_ok());
+    assert_eq!(dag.insert(Node::new(0, None, None, ())), None);
+    assert_eq!(dag.get(&1), None);
+    assert!(dag.get(&2).is_some());
+    assert!(dag.pending().is_empty());
+}
+
+#[test]
+fn pending_pool_of_size_zero_refuses_nodes() {
+    let mut dag = Dag::with_pending_limits(PendingLimits { max_nodes: Some(0), max_age: None });
+    assert_eq!(dag.insert_or_hold(Node::new(1, Some(0), None, ())), Ok(Attachment::Refused { missing: vec![0], evicted: vec![] }));
+    assert!(dag.pending().is_empty());
+    assert_eq!(dag.insert_or_hold(Node::new(0, None, None, ())), Ok(Attachment::Attached { attached: vec![0], evicted: vec![] }));
+    assert_eq!(dag.get(&1), None);
+}
+
+#[test]
+fn dag_becomes_safe_again_once_missing_references_arrive() {
+    let mut dag = Dag::new();
+    assert!(dag.insert(Node::new(2, Some(0), Some(1), ())).is_some());
+    assert!(!dag.is_safe());
+    let report = dag.safety_report();
+    assert_eq!(report.unresolved_references.len(), 2);
+    assert!(report.unresolved_references.contains(&DanglingReference { id: 2, side: Side::Left, missing: 0 }));
+    assert!(report.unresolved_references.contains(&DanglingReference { id: 2, side: Side::Right, missing: 1 }));
+    assert_eq!(dag.insert(Node::new(0, None, None, ())), None);
+    assert_eq!(dag.safety_report().unresolved_references, vec![DanglingReference { id: 2, side: Side::Right, missing: 1 }]);
+    assert_eq!(dag.insert(Node::new(1, Some(0), None, ())), None);
+    assert!(dag.is_safe());
+    assert!(dag.safety_report().is_safe());
+    // Orphans make the dag unsafe until the removed node is inserted again.
+    assert!(dag.remove(&1, RemovalPolicy::Orphan).is_ok());
+    assert_eq!(dag.safety_report().unresolved_references, vec![DanglingReference { id: 2, side: Side::Right, missing: 1 }]);
+    assert_eq!(dag.insert(Node::new(1, None, None, ())), None);
+    assert!(dag.is_safe());
+}
+
+#[test]
+fn safety_report_follows_overwrites() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[Node::new(0, None, None, ()), Node::new(1, Some(0), None, ()), Node::new(2, Some(1), None, ())]);
+    // Re-parenting without closing a cycle keeps the dag safe, but the overwrite is reported.
+    assert!(dag.insert_or_update(Node::new(2, Some(0), None, ())).expect("Invalid value assumption.").is_some());
+    assert!(dag.is_safe());
+    assert_eq!(dag.safety_report().overwritten_ids, vec![2]);
+    // Making node 0 a child of node 2 would close the cycle 0 -> 2 -> 0, so it is rejected.
+    assert_eq!(dag.insert_or_update(Node::new(0, Some(2), None, ())), Err(InsertError::Cycle { id: 0, cycle: vec![0, 2] }));
+    assert_eq!(dag.parents(&0), Some(vec![]));
+    assert!(dag.is_safe());
+    assert!(dag.insert_or_update(Node::new(0, None, None, ())).is_ok());
+    assert_eq!(dag.safety_report().overwritten_ids, vec![2, 0]);
+    // Removed ids are not reported as overwritten anymore.
+    assert!(dag.remove(&2, RemovalPolicy::Refuse).is_ok());
+    assert_eq!(dag.safety_report().overwritten_ids, vec![0]);
+}
+
+#[test]
+fn cycles_closed_by_missing_references_are_reported() {
+    let mut dag = Dag::new();
+    assert!(dag.insert(Node::new(1, Some(0), None, ())).is_some());
+    assert_eq!(dag.insert(Node::new(0, Some(1), None, ())), None);
+    assert!(!dag.is_safe());
+    assert_eq!(dag.safety_report().cycles, vec![vec![0, 1]]);
+    assert!(dag.insert(Node::new(2, Some(2), None, ())).is_some());
+    assert!(dag.safety_report().cycles.contains(&vec![2]));
+    let removal = dag.remove(&0, RemovalPolicy::Cascade).expect("Invalid value assumption.");
+    assert_eq!(removal.removed.len(), 2);
+    assert_eq!(dag.safety_report().cycles, vec![vec![2]]);
+    assert!(dag.remove(&2, RemovalPolicy::Cascade).is_ok());
+    assert!(dag.is_safe());
+}
+
+#[test]
+fn self_referencing_nodes_are_removed_under_refuse() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[Node::new(0, None, None, ()), Node::new(2, Some(2), Some(0), ()), Node::new(3, Some(2), None, ())]);
+    assert_eq!(dag.remove(&2, RemovalPolicy::Refuse).err(), Some(RemovalError::HasDescendants { id: 2, children: vec![3] }));
+    assert!(dag.remove(&3, RemovalPolicy::Refuse).is_ok());
+    let removal = dag.remove(&2, RemovalPolicy::Refuse).expect("Invalid value assumption.");
+    assert_eq!(removal.removed.len(), 1);
+    assert_eq!(dag.get(&2), None);
+    assert!(dag.is_safe());
+}
+
+#[test]
+fn collitions_keep_first_node_by_default() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[Node::new(0, None, None, ()), Node::new(1, Some(0), None, ())]);
+    assert!(dag.insert(Node::new(1, None, None, ())).expect("Invalid value assumption.").has_same_fields_to(&Node::new(1, Some(0), None, ())));
+    assert_eq!(dag.parents(&1), Some(vec![0]));
+    assert_eq!(dag.get_collitions(&1).map(|collitions| { collitions.len() }), Some(1));
+    assert_eq!(dag.collition_decisions(), &[CollitionDecision { id: 1, resolution: Resolution::KeptFirst }]);
+    assert_eq!(dag.take_collition_decisions().len(), 1);
+    assert!(dag.collition_decisions().is_empty());
+    // Re-deliveries of the present node are recorded as any other collition, and nothing is recorded once auditing is disabled.
+    dag.insert_from(&[Node::new(0, None, None, ()), Node::new(1, Some(0), None, ())]);
+    assert_eq!(dag.take_collition_decisions(), vec![
+        CollitionDecision { id: 0, resolution: Resolution::KeptFirst },
+        CollitionDecision { id: 1, resolution: Resolution::KeptFirst },
+    ]);
+    dag.set_collition_audit(false);
+    dag.insert(Node::new(0, Some(1), None, ()));
+    assert!(dag.collition_decisions().is_empty());
+    assert!(dag.get_collitions(&0).expect("Invalid value assumption.").contains(&Node::new(0, Some(1), None, ()).into())); // the policy still applies.
+}
+
+#[test]
+fn collitions_keep_last_node_unless_it_closes_a_cycle() {
+    let mut dag = Dag::new();
+    dag.set_collition_policy(CollitionPolicy::KeepLast);
+    dag.insert_from(&[Node::new(0, None, None, ()), Node::new(1, Some(0), None, ()), Node::new(2, Some(1), None, ())]);
+    // Re-parenting node 1 to None is accepted and the previous node is returned.
+    assert!(dag.insert(Node::new(1, None, None, ())).expect("Invalid value assumption.").has_same_fields_to(&Node::new(1, Some(0), None, ())));
+    assert_eq!(dag.parents(&1), Some(vec![]));
+    assert!(dag.get_collitions(&1).is_none());
+    // Making node 1 a child of node 2 would close the cycle 1 -> 2 -> 1, so node 1 is kept and the newcomer collected.
+    let colliding_node = Node::new(1, Some(2), None, ());
+    assert!(dag.insert(colliding_node).is_some());
+    assert_eq!(dag.parents(&1), Some(vec![]));
+    assert!(dag.get_collitions(&1).expect("Invalid value assumption.").contains(&colliding_node.into()));
+    assert!(dag.is_safe());
+    assert_eq!(dag.collition_decisions(), &[
+        CollitionDecision { id: 1, resolution: Resolution::KeptLast },
+        CollitionDecision { id: 1, resolution: Resolution::CycleRefused { cycle: vec![1, 2] } },
+    ]);
+}
+
+#[test]
+fn collitions_are_rejected_or_merged() {
+    let mut dag = Dag::new();
+    dag.set_collition_policy(CollitionPolicy::Reject);
+    dag.insert_from(&[Node::new(0, None, None, 1), Node::new(1, Some(0), None, 2)]);
+    assert_eq!(dag.insert(Node::new(1, None, None, 3)).map(|node| { node.payload }), Some(2));
+    assert!(dag.get_collitions(&1).is_none());
+    dag.set_collition_policy(CollitionPolicy::merge(|present: &Node<u32, u32>, newcomer: &Node<u32, u32>| {
+        Node::new(present.id, present.left, newcomer.right, present.payload + newcomer.payload)
+    }));
+    assert!(matches!(dag.collition_policy(), CollitionPolicy::Merge(_)));
+    assert_eq!(dag.insert(Node::new(1, None, Some(0), 3)).map(|node| { node.payload }), Some(2));
+    let merged_node = dag.get(&1).expect("Invalid value assumption.");
+    assert!(merged_node.has_same_fields_to(&Node::new(1, Some(0), Some(0), 5)));
+    assert_eq!(merged_node.payload, 5);
+    assert_eq!(dag.children(&0), &[1, 1]);
+    // Merges closing a cycle are refused as well.
+    assert!(dag.insert(Node::new(0, None, Some(1), 0)).is_some());
+    assert_eq!(dag.parents(&0), Some(vec![]));
+    assert_eq!(dag.collition_decisions(), &[
+        CollitionDecision { id: 1, resolution: Resolution::Rejected },
+        CollitionDecision { id: 1, resolution: Resolution::Merged },
+        CollitionDecision { id: 0, resolution: Resolution::CycleRefused { cycle: vec![0, 1] } },
+    ]);
+}
+
+#[test]
+fn merges_changing_the_id_are_refused() {
+    use std::sync::{
+        Arc,
+        atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    };
+    let merges = Arc::new(AtomicUsize::new(0));
+    let merge_count = Arc::clone(&merges);
+    let mut dag = Dag::new();
+    dag.set_collition_policy(CollitionPolicy::merge(move |present: &Node<u32, u32>, newcomer: &Node<u32, u32>| {
+        merge_count.fetch_add(1, Ordering::Relaxed); // merge functions can capture state.
+        Node::new(present.id + newcomer.payload, present.left, present.right, present.payload)
+    }));
+    dag.insert_from(&[Node::new(0, None, None, 0), Node::new(1, Some(0), None, 0)]);
+    assert!(dag.insert(Node::new(1, None, None, 0)).is_some());
+    assert_eq!(dag.parents(&1), Some(vec![0]));
+    let refused_node = Node::new(1, None, None, 2);
+    assert!(dag.insert(refused_node).is_some());
+    assert!(dag.get(&1).expect("Invalid value assumption.").has_same_fields_to(&Node::new(1, Some(0), None, 0)));
+    assert!(!dag.contains_id(&3));
+    assert!(dag.get_collitions(&1).expect("Invalid value assumption.").contains(&refused_node.into()));
+    assert_eq!(merges.load(Ordering::Relaxed), 2);
+    assert_eq!(dag.collition_decisions(), &[
+        CollitionDecision { id: 1, resolution: Resolution::Merged },
+        CollitionDecision { id: 1, resolution: Resolution::MergeRefused { merged_id: 3 } },
+    ]);
+}
+
+#[test]
+fn equivocations_report_field_level_diffs_and_descendants() {
+    let stored = Node::new(2, Some(0), Some(1), String::from("pay alice"));
+    let forged_payload = Node::new(2, Some(0), Some(1), String::from("pay mallory"));
+    let forged_references = Node::new(2, Some(1), None, String::from("pay alice"));
+    let mut dag = Dag::new();
+    dag.insert_from(&[
+        Node::new(0, None, None, String::from("genesis")),
+        Node::new(1, Some(0), None, String::from("first")),
+        stored.clone(),
+        Node::new(3, Some(2), None, String::from("second")),
+        Node::new(4, Some(3), Some(1), String::from("third")),
+    ]);
+    assert!(dag.equivocations().is_empty());
+    // Exact duplicates are not equivocations.
+    assert!(dag.insert(stored.clone()).is_some());
+    assert!(dag.equivocations().is_empty());
+    assert!(dag.insert(forged_payload.clone()).is_some());
+    assert!(dag.insert(forged_references.clone()).is_some());
+    assert_eq!(dag.descendants(&2), vec![3, 4]);
+    let equivocations = dag.equivocations();
+    assert_eq!(equivocations, vec![ // sorted by diff.
+        Equivocation {
+            id: 2,
+            stored: stored.clone(),
+            conflicting: forged_payload,
+            diff: FieldDiff {
+                stored_only_references: vec![],
+                conflicting_only_references: vec![],
+                payload_differs: true,
+            },
+            stored_descendants: vec![3, 4],
+            conflicting_descendants: vec![], // collected variants were never stored, so nothing builds on them.
+        },
+        Equivocation {
+            id: 2,
+            stored,
+            conflicting: forged_references,
+            diff: FieldDiff {
+                stored_only_references: vec![(Side::Left, 0), (Side::Right, 1)],
+                conflicting_only_references: vec![(Side::Left, 1)],
+                payload_differs: false,
+            },
+            stored_descendants: vec![3, 4],
+            conflicting_descendants: vec![],
+        },
+    ]);
+    // The evidence can be exported and read back.
+    let evidence = serde_json::to_string(&equivocations).expect("Invalid value assumption.");
+    assert!(evidence.contains("pay mallory"));
+    let imported: Vec<Equivocation<u32, Node<u32, String>>> = serde_json::from_str(&evidence).expect("Invalid value assumption.");
+    assert_eq!(imported.len(), 2);
+    assert!(imported[0].conflicting.has_same_fields_to(&equivocations[0].conflicting));
+    assert_eq!(imported[0].conflicting.payload, "pay mallory");
+}
+
+#[test]
+fn equivocations_are_kept_whatever_the_policy() {
+    let genesis = Node::new(0, None, None, 0);
+    let first_variant = Node::new(1, Some(0), None, 1);
+    let second_variant = Node::new(1, Some(0), None, 2);
+    let third_variant = Node::new(1, Some(0), None, 3);
+    let mut dag = Dag::new();
+    dag.set_collition_policy(CollitionPolicy::KeepLast);
+    dag.insert_from(&[genesis, first_variant, Node::new(2, Some(1), None, 0), Node::new(3, Some(2), None, 0)]);
+    // Node 4 builds on the second variant, which replaces the first one.
+    assert!(dag.insert(second_variant).is_some());
+    assert!(dag.insert(Node::new(4, Some(1), None, 0)).is_none());
+    assert!(dag.insert(second_variant).is_some()); // re-deliveries are not equivocations.
+    // The third variant is rejected, but kept as evidence.
+    dag.set_collition_policy(CollitionPolicy::Reject);
+    assert!(dag.insert(third_variant).is_some());
+    assert!(dag.get_collitions(&1).is_none());
+    assert_eq!(dag.get(&1).map(|node| { node.payload }), Some(2));
+    let equivocations = dag.equivocations();
+    assert_eq!(equivocations.len(), 2);
+    assert_eq!(equivocations[0].conflicting.payload, 1); // equal diffs keep the order the variants arrived in.
+    assert_eq!(equivocations[0].stored_descendants, vec![4]);
+    assert_eq!(equivocations[0].conflicting_descendants, vec![2, 3]);
+    assert_eq!(equivocations[1].conflicting.payload, 3);
+    assert!(equivocations[1].conflicting_descendants.is_empty());
+    // Variants of different ids are sorted by id.
+    assert!(dag.insert(Node::new(0, None, None, 9)).is_some());
+    let ids: Vec<u32> = dag.equivocations().into_iter().map(|equivocation| { equivocation.id }).collect();
+    assert_eq!(ids, vec![0, 1, 1]);
+}
+
+#[test]
+fn order_is_maintained_under_insertions_in_any_order() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[Node::new(3, Some(2), None, ()), Node::new(2, Some(1), Some(0), ()), Node::new(4, None, None, ())]);
+    assert_eq!(dag.order(), vec![2, 3, 4]); // node 3 is moved after its parent, while references to missing nodes don't constrain the order.
+    assert!(dag.insert(Node::new(0, None, None, ())).is_none());
+    assert!(dag.insert(Node::new(1, Some(4), None, ())).is_none());
+    assert_eq!(dag.order(), vec![0, 4, 1, 2, 3]);
+    assert!(dag.precedes(&0, &3));
+    assert!(!dag.precedes(&3, &0));
+    assert!(!dag.precedes(&0, &5));
+    // Re-parenting node 4 under node 0 keeps the order valid, but making it a child of node 3 is rejected.
+    assert!(dag.insert_or_update(Node::new(4, Some(0), None, ())).is_ok());
+    assert_eq!(dag.order(), vec![0, 4, 1, 2, 3]);
+    assert_eq!(dag.insert_or_update(Node::new(4, Some(3), None, ())), Err(InsertError::Cycle { id: 4, cycle: vec![4, 1, 2, 3] }));
+    assert!(dag.remove(&2, RemovalPolicy::Cascade).is_ok());
+    assert_eq!(dag.order(), vec![0, 4, 1]);
+}
+
+#[test]
+fn order_skips_cycles_until_they_are_broken() {
+    let mut dag = Dag::new();
+    assert!(dag.insert(Node::new(1, Some(0), None, ())).is_some());
+    assert_eq!(dag.insert(Node::new(2, Some(1), None, ())), None);
+    assert_eq!(dag.insert(Node::new(0, Some(2), None, ())), None);
+    assert!(!dag.is_safe());
+    assert_eq!(dag.order(), vec![1, 2, 0]); // the edge 0 -> 1 closes the cycle, so it is left out of the order.
+    assert!(dag.insert_or_update(Node::new(0, None, None, ())).is_ok());
+    assert!(dag.is_safe());
+    assert_eq!(dag.order(), vec![0, 1, 2]);
+}
+
+#[test]
+fn pseudo_random_order_of_shuffled_insertions() {
+    let mut rng = rand_pcg::Pcg32::seed_from_u64(2);
+    let mut vertices: Vec<Node<u32, ()>> = (0..2_000).map(|i| {
+        if i > 10 {
+            Node::new(i, Some(rng.gen_range(0..i)), Some(rng.gen_range(0..i)), ())
+        } else {
+            Node::new(i, None, None, ())
+        }
+    }).collect();
+    vertices.shuffle(&mut rng);
+    let mut dag = Dag::new();
+    dag.insert_from(&vertices);
+    assert!(dag.is_safe());
+    let order = dag.order();
+    assert_eq!(order.len(), vertices.len());
+    for node in vertices.iter() {
+        for ancestor in node.parents() {
+            assert!(dag.precedes(&ancestor, &node.id));
+        };
+    };
+    assert!(Dag::check_topological_order(&order.iter().map(|id| { *dag.get(id).expect("Invalid value assumption.") }).collect::<Vec<Node<u32, ()>>>()));
+}
+
+#[test]
+fn set_parents_rejects_cycles() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[
+        Node::new(0, None, None, "a"),
+        Node::new(1, Some(0), None, "b"),
+        Node::new(2, Some(1), None, "c"),
+        Node::new(3, None, None, "d"),
+    ]);
+    assert_eq!(dag.set_parents(&3, Some(2), Some(0)), Ok((None, None)));
+    assert_eq!(dag.children(&2), &[3]);
+    assert!(dag.precedes(&2, &3));
+    assert_eq!(dag.set_parents(&0, None, Some(3)), Err(UpdateError::Cycle { id: 0, cycle: vec![0, 3] }));
+    assert_eq!(dag.set_parents(&1, Some(1), None), Err(UpdateError::Cycle { id: 1, cycle: vec![1] }));
+    assert_eq!(dag.set_parents(&5, None, None), Err(UpdateError::NotFound(5)));
+    assert_eq!(dag.parents(&0), Some(vec![]));
+    assert!(dag.is_safe());
+    assert!(dag.safety_report().overwritten_ids.is_empty());
+    // Missing references make the dag unsafe, as inserting a node referencing them does.
+    assert_eq!(dag.set_parents(&1, Some(7), None), Ok((Some(0), None)));
+    assert!(!dag.is_safe());
+    assert_eq!(dag.set_parents(&1, Some(0), None), Ok((Some(7), None)));
+    assert!(dag.is_safe());
+}
+
+#[test]
+fn set_payload_keeps_references_and_safety() {
+    let mut dag = Dag::new();
+    dag.insert_from(&[Node::new(0, None, None, 1), Node::new(1, Some(0), None, 2)]);
+    assert_eq!(dag.set_payload(&1, 20), Ok(2));
+    assert_eq!(dag.get(&1).map(|node| { node.payload }), Some(20));
+    assert_eq!(dag.parents(&1), Some(vec![0]));
+    assert_eq!(dag.set_payload(&2, 30), Err(UpdateError::NotFound(2)));
+    assert!(dag.is_safe());
+    let mut nary_dag = Dag::new();
+    nary_dag.insert_from(&[NaryNode::new(0, vec![], 'a'), NaryNode::new(1, vec![], 'b'), NaryNode::new(2, vec![0], 'c')]);
+    assert_eq!(nary_dag.set_payload(&2, 'z'), Ok('c'));
+    assert_eq!(nary_dag.set_parents(&2, vec![0, 1]), Ok(vec![0]));
+    assert_eq!(nary_dag.set_parents(&0, vec![2]), Err(UpdateError::Cycle { id: 0, cycle: vec![0, 2] }));
+    assert_eq!(nary_dag.order().last(), Some(&2));
+    assert!(nary_dag.is_safe());
+}
+
+#[test]
+fn dag_is_analyzed_without_rebuilding_from_slices() {
+    let node_list = [
+        Node::new(0, None, None, ()),
+        Node::new(1, Some(0), None, ()),
+        Node::new(2, None, Some(0), ()),
+        Node::new(3, Some(0), Some(1), ()),
+        Node::new(4, Some(2), Some(1), ()),
+        Node::new(5, Some(3), Some(4), ()),
+    ];
+    let mut dag = Dag::new();
+    dag.insert_from(&node_list);
+    assert_eq!(dag.len(), 6);
+    assert_eq!(dag.nodes().map(|node| { node.id }).collect::<Vec<u32>>(), dag.order());
+    let Ok(Some(ordering)) = dag.sort() else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert!(Dag::check_topological_order(&ordering));
+    let Ok(Some(lengths)) = dag.shortest_and_longest_paths(&0) else {
+        panic!("Invalid topological assumptions for this test data.")
+    };
+    assert_eq!(lengths[&5], (Some(2), Some(3)));
+    assert_eq!(lengths[&3], (Some(1), Some(2)));
+    assert!(dag.shortest_and_longest_paths(&3).is_err());
+    assert!(matches!(dag.shortest_and_longest_paths(&9), Ok(None)));
+    let topology = Topology::from_slice(&node_list).expect("Invalid topological assumptions for this test data.");
+    assert_eq!(dag.bfs_all_paths(&0), Topology::bfs_all_paths(&topology, 0));
+    // The topology of a dag with unresolved references is not consistent.
+    assert!(Topology::from(&dag).validate().is_consistent());
+    dag.insert(Node::new(6, Some(7), None, ()));
+    assert!(!Topology::from(&dag).validate().is_consistent());
+    assert!(matches!(dag.sort(), Ok(None)));
+}
+
+#[test]
+#[allow(unused_variables)] // baseline test, kept as written.
 fn pseudo_random_unconnected_vertices_count() {
     let mut rng = rand_pcg::Pcg32::seed_from_u64(1);
     let mut vertices: Vec<Node<u32, ()>> = Vec::new();
@@ -179,6 +935,7 @@ fn pseudo_random_unconnected_vertices_count() {
 }
 
 #[test]
+#[allow(unused_variables)] // baseline test, kept as written.
 fn pseudo_random_connected_vertices_count() {
     let mut rng = rand_pcg::Pcg32::seed_from_u64(1);
     let mut vertices: Vec<Node<u32, ()>> = Vec::new();
@@ -199,6 +956,7 @@ fn pseudo_random_connected_vertices_count() {
 }
 
 #[test]
+#[allow(unused_variables)] // baseline test, kept as written.
 fn pseudo_random_connected_vertices_count_with_payload() {
     let mut rng = rand_pcg::Pcg32::seed_from_u64(1);
     let mut vertices: Vec<Node<u32, u32>> = Vec::new();